@@ -0,0 +1,114 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use heaps::{BinaryHeap, DaryHeap, IndexedHeap, PairingHeap};
+
+const SIZES : [usize; 2] = [1_000, 100_000];
+
+fn random_keys(n : usize) -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(0);
+    (0..n).map(|_| rng.gen_range(0, 1 << 40)).collect()
+}
+
+// pushes every key and then pops all of them
+fn insert_pop(c : &mut Criterion) {
+    let mut group = c.benchmark_group("insert_pop");
+
+    for &n in SIZES.iter() {
+        let keys = random_keys(n);
+
+        group.bench_with_input(BenchmarkId::new("BinaryHeap", n), &keys, |b, keys| b.iter(|| {
+            let mut heap = BinaryHeap::new();
+            keys.iter().for_each(|&k| heap.insert(k));
+            (0..n).map(|_| heap.pop().unwrap()).fold(0, u64::wrapping_add)
+        }));
+        group.bench_with_input(BenchmarkId::new("IndexedHeap", n), &keys, |b, keys| b.iter(|| {
+            let mut heap = IndexedHeap::new();
+            keys.iter().for_each(|&k| { heap.insert(k); });
+            (0..n).map(|_| heap.pop().unwrap().1).fold(0, u64::wrapping_add)
+        }));
+        group.bench_with_input(BenchmarkId::new("PairingHeap", n), &keys, |b, keys| b.iter(|| {
+            let mut heap = PairingHeap::new();
+            keys.iter().for_each(|&k| { heap.insert(k); });
+            (0..n).map(|_| heap.pop().unwrap().1).fold(0, u64::wrapping_add)
+        }));
+    }
+
+    group.finish();
+}
+
+// Dijkstra-like mix: n inserts, 4n decrease-keys and n pops, where BinaryHeap pushes duplicates lazily instead
+fn decrease_key_mix(c : &mut Criterion) {
+    let mut group = c.benchmark_group("decrease_key_mix");
+
+    for &n in SIZES.iter() {
+        let keys = random_keys(n);
+        let mut rng = StdRng::seed_from_u64(1);
+        let updates : Vec<(usize, u64)> = (0..4 * n).map(|_| (rng.gen_range(0, n), rng.gen_range(0, 1 << 20))).collect();
+
+        group.bench_with_input(BenchmarkId::new("BinaryHeap", n), &updates, |b, updates| b.iter(|| {
+            let mut best = keys.clone();
+            let mut heap = BinaryHeap::new();
+            keys.iter().enumerate().for_each(|(i, &k)| heap.insert((k, i)));
+            for &(i, d) in updates.iter() {
+                best[i] /= d + 2;
+                heap.insert((best[i], i));
+            }
+
+            let mut sum = 0u64;
+            while let Some((k, i)) = heap.pop() {
+                if k == best[i] {
+                    sum = sum.wrapping_add(k);
+                    best[i] = u64::MAX;
+                }
+            }
+            sum
+        }));
+        group.bench_with_input(BenchmarkId::new("IndexedHeap", n), &updates, |b, updates| b.iter(|| {
+            let mut heap = IndexedHeap::new();
+            let handles : Vec<_> = keys.iter().map(|&k| heap.insert(k)).collect();
+            for &(i, d) in updates.iter() {
+                let k = *heap.get(handles[i]).unwrap();
+                heap.decrease_key(handles[i], k / (d + 2));
+            }
+            (0..n).map(|_| heap.pop().unwrap().1).fold(0, u64::wrapping_add)
+        }));
+        group.bench_with_input(BenchmarkId::new("PairingHeap", n), &updates, |b, updates| b.iter(|| {
+            let mut heap = PairingHeap::new();
+            let handles : Vec<_> = keys.iter().map(|&k| heap.insert(k)).collect();
+            for &(i, d) in updates.iter() {
+                let k = *heap.get(handles[i]).unwrap();
+                heap.decrease_key(handles[i], k / (d + 2));
+            }
+            (0..n).map(|_| heap.pop().unwrap().1).fold(0, u64::wrapping_add)
+        }));
+    }
+
+    group.finish();
+}
+
+fn dary_insert_pop<const D : usize>(keys : &[u64]) -> u64 {
+    let mut heap = DaryHeap::<_, D>::new();
+    keys.iter().for_each(|&k| heap.insert(k));
+    std::iter::from_fn(|| heap.pop()).fold(0, u64::wrapping_add)
+}
+
+// compares the arities on a queue large enough to fall out of the cache
+fn arity(c : &mut Criterion) {
+    let mut group = c.benchmark_group("arity");
+    let n = 1_000_000;
+    let keys = random_keys(n);
+
+    group.sample_size(10);
+    group.bench_with_input(BenchmarkId::new("D=2", n), &keys, |b, keys| b.iter(|| dary_insert_pop::<2>(keys)));
+    group.bench_with_input(BenchmarkId::new("D=4", n), &keys, |b, keys| b.iter(|| dary_insert_pop::<4>(keys)));
+    group.bench_with_input(BenchmarkId::new("D=8", n), &keys, |b, keys| b.iter(|| dary_insert_pop::<8>(keys)));
+    group.bench_with_input(BenchmarkId::new("D=4 heapify", n), &keys, |b, keys| b.iter(|| {
+        DaryHeap::<_, 4>::from_vec(keys.clone()).into_sorted_vec()
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, insert_pop, decrease_key_mix, arity);
+criterion_main!(benches);
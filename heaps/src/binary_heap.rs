@@ -1,123 +1,10 @@
+use crate::compare::*;
+use crate::dary_heap::DaryHeap;
 
-#[derive(Debug)]
-pub struct BinaryHeap<T : Ord> {
-    src : Vec<T>,
-    n : usize
-}
+pub type BinaryHeap<T, C = MinOrder> = DaryHeap<T, 2, C>;
 
-impl<T : Ord> BinaryHeap<T> {
-    pub fn new() -> Self {
-        Self{ src : vec![], n : 0 }
-    }
-
-    pub fn from_vec(v : Vec<T>) -> Self {
-        let len = v.len();
-        let mut heap = Self{ src : v, n : len };
-
-        for i in (0..(len/2)).rev() {
-            heap.trickle_down(i);
-        }
-
-        return heap;
-    }
-
-    pub fn into_vec(self) -> Vec<T> {
-        self.src
-    }
-
-    fn left(idx : usize) -> usize {
-        (idx << 1) + 1
-    }
-    fn right(idx : usize) -> usize {
-        (idx << 1) + 2
-    }
-    fn parent(idx : usize) -> usize {
-        (idx - 1) >> 1
-    }
-
-    pub fn is_empty(&self) -> bool {
-        self.n == 0
-    }
-
-    fn bubble_up(&mut self) {
-        if self.n == 0 {
-            unreachable!();
-        }
-        if self.n == 1 {
-            return;
-        }
-
-        let mut i = self.n - 1;
-        let mut p = Self::parent(i);
-
-        while i > 0 && self.src[i] < self.src[p] {
-            self.src.swap(i, p);
-            i = p;
-
-            if p != 0 {
-                p = Self::parent(p);
-            }
-        }
-    }
-
-    pub fn insert(&mut self, x : T) {
-        self.src.push(x);
-        self.n += 1;
-
-        self.bubble_up();
-    }
-
-    fn trickle_down(&mut self, i : usize) {
-        let mut i = i;
-        while Self::left(i) < self.n {
-            let l = Self::left(i);
-            let r = Self::right(i);
-
-            if r >= self.n {
-                if self.src[i] > self.src[l] {
-                    self.src.swap(i, l);
-                }
-
-                break;
-            }
-
-            if self.src[l] < self.src[r] {
-                if self.src[i] > self.src[l] {
-                    self.src.swap(i, l);
-                    i = l;
-                }
-                else {
-                    break;
-                }
-            }
-            else {
-                if self.src[i] > self.src[r] {
-                    self.src.swap(i, r);
-                    i = r;
-                }
-                else {
-                    break;
-                }
-            }
-        }
-    }
-
-    pub fn pop(&mut self) -> Option<T> {
-        if self.is_empty() {
-            return None;
-        }
-
-        self.src.swap(self.n - 1, 0);
-
-        let x = self.src.pop();
-
-        self.n -= 1;
-
-        self.trickle_down(0);
-
-        x
-    }
-}
+pub type MinHeap<T> = BinaryHeap<T, MinOrder>;
+pub type MaxHeap<T> = BinaryHeap<T, MaxOrder>;
 
 #[cfg(test)]
 mod tests {
@@ -148,5 +35,34 @@ mod tests {
         assert_eq!(heap.pop(), Some(5));
         assert_eq!(heap.pop(), Some(9));
         assert_eq!(heap.pop(), None);
+
+        let mut max_heap = MaxHeap::from(vec![5, 3, 4, 9, -1, 0]);
+        max_heap.insert(7);
+
+        assert_eq!(max_heap.pop(), Some(9));
+        assert_eq!(max_heap.pop(), Some(7));
+        assert_eq!(max_heap.pop(), Some(5));
+
+        let mut max_heap = MaxHeap::default();
+        max_heap.extend(vec![2, 8, 5]);
+        assert_eq!(max_heap.pop(), Some(8));
+        assert_eq!(MaxHeap::with_comparator_and_capacity(MaxOrder, 4).pop(), None::<i32>);
+        assert_eq!(MaxHeap::from(vec![1, 3, 2]).into_sorted_vec(), vec![3, 2, 1]);
+
+        let mut jobs = BinaryHeap::by_key(|job : &(&str, u32)| job.1);
+        jobs.insert(("build", 3));
+        jobs.insert(("deploy", 5));
+        jobs.insert(("test", 1));
+
+        assert_eq!(jobs.pop(), Some(("test", 1)));
+        assert_eq!(jobs.pop(), Some(("build", 3)));
+
+        let mut by_len = BinaryHeap::by(|a : &String, b : &String| b.len().cmp(&a.len()));
+        by_len.insert("ab".to_string());
+        by_len.insert("abcd".to_string());
+        by_len.insert("a".to_string());
+
+        assert_eq!(by_len.pop(), Some("abcd".to_string()));
+        assert_eq!(by_len.pop(), Some("ab".to_string()));
     }
 }
\ No newline at end of file
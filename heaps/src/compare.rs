@@ -0,0 +1,53 @@
+use std::cmp::Ordering;
+
+// the heaps keep the least element w.r.t. the comparator on the top
+pub trait Compare<T> {
+    fn compare(&self, a : &T, b : &T) -> Ordering;
+
+    fn less(&self, a : &T, b : &T) -> bool {
+        self.compare(a, b) == Ordering::Less
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinOrder;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxOrder;
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyOrder<F>(pub F);
+
+// flips another comparator, so that the greatest element comes on the top
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reversed<C>(pub C);
+
+impl<T : Ord> Compare<T> for MinOrder {
+    fn compare(&self, a : &T, b : &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl<T : Ord> Compare<T> for MaxOrder {
+    fn compare(&self, a : &T, b : &T) -> Ordering {
+        b.cmp(a)
+    }
+}
+
+impl<T, K : Ord, F : Fn(&T) -> K> Compare<T> for KeyOrder<F> {
+    fn compare(&self, a : &T, b : &T) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
+
+impl<T, C : Compare<T>> Compare<T> for Reversed<C> {
+    fn compare(&self, a : &T, b : &T) -> Ordering {
+        self.0.compare(b, a)
+    }
+}
+
+impl<T, F : Fn(&T, &T) -> Ordering> Compare<T> for F {
+    fn compare(&self, a : &T, b : &T) -> Ordering {
+        self(a, b)
+    }
+}
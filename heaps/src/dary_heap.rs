@@ -0,0 +1,429 @@
+use std::cmp::Ordering;
+use std::ops::{Deref, DerefMut};
+use crate::compare::*;
+
+// every node has D children, stored in src[D * i + 1..=D * i + D]
+#[derive(Debug)]
+pub struct DaryHeap<T, const D : usize, C : Compare<T> = MinOrder> {
+    src : Vec<T>,
+    cmp : C
+}
+
+pub(crate) fn child<const D : usize>(idx : usize, k : usize) -> usize {
+    D * idx + k + 1
+}
+pub(crate) fn parent<const D : usize>(idx : usize) -> usize {
+    (idx - 1) / D
+}
+
+// moves src[i] up while it is less than its parent, and returns where it stopped
+pub(crate) fn bubble_up_at<const D : usize, T, L, S>(src : &mut [T], i : usize, mut less : L, mut swap : S) -> usize
+    where L : FnMut(&T, &T) -> bool, S : FnMut(&mut [T], usize, usize)
+{
+    let mut i = i;
+    while i > 0 {
+        let p = parent::<D>(i);
+        if !less(&src[i], &src[p]) {
+            break;
+        }
+
+        swap(src, i, p);
+        i = p;
+    }
+
+    i
+}
+
+// moves src[i] down while one of its children is less than it, and returns where it stopped
+pub(crate) fn trickle_down_at<const D : usize, T, L, S>(src : &mut [T], i : usize, mut less : L, mut swap : S) -> usize
+    where L : FnMut(&T, &T) -> bool, S : FnMut(&mut [T], usize, usize)
+{
+    let n = src.len();
+    let mut i = i;
+    while child::<D>(i, 0) < n {
+        let first = child::<D>(i, 0);
+        let last = n.min(child::<D>(i, D - 1) + 1);
+
+        let mut c = first;
+        for j in (first + 1)..last {
+            if less(&src[j], &src[c]) {
+                c = j;
+            }
+        }
+        if !less(&src[c], &src[i]) {
+            break;
+        }
+
+        swap(src, i, c);
+        i = c;
+    }
+
+    i
+}
+
+// reorders src into a d-ary heap w.r.t. less in O(n) time
+pub fn heapify<const D : usize, T, L>(src : &mut [T], less : L)
+    where L : FnMut(&T, &T) -> bool
+{
+    heapify_with::<D, _, _, _>(src, less, <[T]>::swap);
+}
+
+// heapify that moves the elements only through swap, e.g. to count the swaps
+pub fn heapify_with<const D : usize, T, L, S>(src : &mut [T], mut less : L, mut swap : S)
+    where L : FnMut(&T, &T) -> bool, S : FnMut(&mut [T], usize, usize)
+{
+    if src.len() < 2 {
+        return;
+    }
+
+    for i in (0..=parent::<D>(src.len() - 1)).rev() {
+        trickle_down_at::<D, _, _, _>(src, i, &mut less, &mut swap);
+    }
+}
+
+// turns a d-ary heap w.r.t. less into the descending order by moving the top to the back repeatedly
+pub fn sort_heap<const D : usize, T, L>(src : &mut [T], less : L)
+    where L : FnMut(&T, &T) -> bool
+{
+    sort_heap_with::<D, _, _, _>(src, less, <[T]>::swap);
+}
+
+pub fn sort_heap_with<const D : usize, T, L, S>(src : &mut [T], mut less : L, mut swap : S)
+    where L : FnMut(&T, &T) -> bool, S : FnMut(&mut [T], usize, usize)
+{
+    for end in (1..src.len()).rev() {
+        swap(src, 0, end);
+        trickle_down_at::<D, _, _, _>(&mut src[..end], 0, &mut less, &mut swap);
+    }
+}
+
+// restores a d-ary heap w.r.t. less after its top src[0] is replaced
+pub fn trickle_down_top_with<const D : usize, T, L, S>(src : &mut [T], less : L, swap : S)
+    where L : FnMut(&T, &T) -> bool, S : FnMut(&mut [T], usize, usize)
+{
+    trickle_down_at::<D, _, _, _>(src, 0, less, swap);
+}
+
+// other comparators are given through with_comparator, or Default and From<Vec<T>> when they have no state
+impl<T : Ord, const D : usize> DaryHeap<T, D> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+
+    pub fn with_capacity(capacity : usize) -> Self {
+        Self::with_comparator_and_capacity(MinOrder, capacity)
+    }
+
+    pub fn from_vec(v : Vec<T>) -> Self {
+        Self::from_vec_with(v, MinOrder)
+    }
+}
+
+impl<T, F : Fn(&T, &T) -> Ordering, const D : usize> DaryHeap<T, D, F> {
+    pub fn by(f : F) -> Self {
+        Self::with_comparator(f)
+    }
+}
+
+impl<T, K : Ord, F : Fn(&T) -> K, const D : usize> DaryHeap<T, D, KeyOrder<F>> {
+    pub fn by_key(f : F) -> Self {
+        Self::with_comparator(KeyOrder(f))
+    }
+}
+
+impl<T, C : Compare<T>, const D : usize> DaryHeap<T, D, C> {
+    pub fn with_comparator(cmp : C) -> Self {
+        Self::with_comparator_and_capacity(cmp, 0)
+    }
+
+    pub fn with_comparator_and_capacity(cmp : C, capacity : usize) -> Self {
+        assert!(D >= 2, "arity of a heap must be at least 2");
+
+        Self{ src : Vec::with_capacity(capacity), cmp }
+    }
+
+    pub fn from_vec_with(v : Vec<T>, cmp : C) -> Self {
+        let mut heap = Self::with_comparator(cmp);
+        heap.src = v;
+        heap.rebuild();
+
+        heap
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.src
+    }
+
+    // returns the elements in the order pop would return them, sorting in place
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let cmp = &self.cmp;
+        sort_heap::<D, _, _>(&mut self.src, |a, b| cmp.less(a, b));
+
+        self.src.reverse();
+        self.src
+    }
+
+    pub fn comparator(&self) -> &C {
+        &self.cmp
+    }
+
+    pub fn len(&self) -> usize {
+        self.src.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.src.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.src.capacity()
+    }
+
+    pub fn clear(&mut self) {
+        self.src.clear();
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.src.first()
+    }
+
+    // the top element may be modified through the returned guard, and the heap is fixed when it is dropped
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T, D, C>> {
+        if self.is_empty() { None } else { Some(PeekMut { heap : self }) }
+    }
+
+    fn bubble_up(&mut self, i : usize) {
+        let cmp = &self.cmp;
+        bubble_up_at::<D, _, _, _>(&mut self.src, i, |a, b| cmp.less(a, b), |src, i, j| src.swap(i, j));
+    }
+
+    fn trickle_down(&mut self, i : usize) {
+        let cmp = &self.cmp;
+        trickle_down_at::<D, _, _, _>(&mut self.src, i, |a, b| cmp.less(a, b), |src, i, j| src.swap(i, j));
+    }
+
+    fn rebuild(&mut self) {
+        let cmp = &self.cmp;
+        heapify::<D, _, _>(&mut self.src, |a, b| cmp.less(a, b));
+    }
+
+    // restores the heap after src[start..] are pushed, by either heapify in O(n) or bubbling up each in O(k log n)
+    fn rebuild_tail(&mut self, start : usize) {
+        let n = self.src.len();
+        let k = n - start;
+        let log_n = (usize::BITS - n.leading_zeros()) as usize;
+
+        if k * log_n > 2 * n {
+            self.rebuild();
+        }
+        else {
+            for i in start..n {
+                self.bubble_up(i);
+            }
+        }
+    }
+
+    pub fn insert(&mut self, x : T) {
+        self.src.push(x);
+        self.bubble_up(self.src.len() - 1);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let last = self.src.len() - 1;
+        self.src.swap(last, 0);
+
+        let x = self.src.pop();
+
+        self.trickle_down(0);
+
+        x
+    }
+
+    // moves all elements of other into self
+    pub fn append(&mut self, other : &mut Self) {
+        if self.len() < other.len() {
+            std::mem::swap(&mut self.src, &mut other.src);
+        }
+
+        let start = self.src.len();
+        self.src.append(&mut other.src);
+        self.rebuild_tail(start);
+    }
+
+    // keeps only the elements satisfying f
+    pub fn retain<F : FnMut(&T) -> bool>(&mut self, f : F) {
+        let len = self.src.len();
+        self.src.retain(f);
+
+        if self.src.len() < len {
+            self.rebuild();
+        }
+    }
+
+    // pops the elements one by one, and the ones left when the iterator is dropped are removed as well
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T, D, C> {
+        DrainSorted { heap : self }
+    }
+}
+
+pub struct PeekMut<'a, T, const D : usize, C : Compare<T>> {
+    heap : &'a mut DaryHeap<T, D, C>
+}
+
+impl<'a, T, const D : usize, C : Compare<T>> PeekMut<'a, T, D, C> {
+    pub fn pop(this : Self) -> T {
+        let x = this.heap.pop().unwrap();
+        std::mem::forget(this);
+
+        x
+    }
+}
+
+impl<'a, T, const D : usize, C : Compare<T>> Deref for PeekMut<'a, T, D, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.heap.src[0]
+    }
+}
+
+impl<'a, T, const D : usize, C : Compare<T>> DerefMut for PeekMut<'a, T, D, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.heap.src[0]
+    }
+}
+
+impl<'a, T, const D : usize, C : Compare<T>> Drop for PeekMut<'a, T, D, C> {
+    fn drop(&mut self) {
+        self.heap.trickle_down(0);
+    }
+}
+
+pub struct DrainSorted<'a, T, const D : usize, C : Compare<T>> {
+    heap : &'a mut DaryHeap<T, D, C>
+}
+
+impl<'a, T, const D : usize, C : Compare<T>> Iterator for DrainSorted<'a, T, D, C> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.heap.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<'a, T, const D : usize, C : Compare<T>> ExactSizeIterator for DrainSorted<'a, T, D, C> {}
+
+impl<'a, T, const D : usize, C : Compare<T>> Drop for DrainSorted<'a, T, D, C> {
+    fn drop(&mut self) {
+        self.heap.clear();
+    }
+}
+
+impl<T, C : Compare<T>, const D : usize> Extend<T> for DaryHeap<T, D, C> {
+    fn extend<I : IntoIterator<Item = T>>(&mut self, iter : I) {
+        let start = self.src.len();
+        self.src.extend(iter);
+        self.rebuild_tail(start);
+    }
+}
+
+impl<T, C : Compare<T> + Default, const D : usize> Default for DaryHeap<T, D, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C : Compare<T> + Default, const D : usize> From<Vec<T>> for DaryHeap<T, D, C> {
+    fn from(v : Vec<T>) -> Self {
+        Self::from_vec_with(v, C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<const D : usize>() {
+        let v : Vec<i32> = (0..200).map(|i| (i * 37) % 101 - 50).collect();
+        let mut sorted = v.clone();
+        sorted.sort();
+
+        let mut heap = DaryHeap::<_, D>::new();
+        for &x in v.iter() {
+            heap.insert(x);
+        }
+        let popped : Vec<_> = std::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(popped, sorted);
+
+        assert_eq!(DaryHeap::<_, D>::from_vec(v.clone()).into_sorted_vec(), sorted);
+
+        let max_heap = DaryHeap::<_, D, MaxOrder>::from(v.clone());
+        sorted.reverse();
+        assert_eq!(max_heap.into_sorted_vec(), sorted);
+    }
+
+    #[test]
+    fn dary_heap_works() {
+        check::<2>();
+        check::<3>();
+        check::<4>();
+        check::<8>();
+
+        let mut heap = DaryHeap::<_, 4>::from_vec(vec![5, 3, 4, 9, -1, 0]);
+        heap.insert(-3);
+
+        assert_eq!(heap.pop(), Some(-3));
+        assert_eq!(heap.pop(), Some(-1));
+        assert_eq!(heap.pop(), Some(0));
+
+        let mut jobs = DaryHeap::<_, 4, _>::by_key(|job : &(&str, u32)| job.1);
+        jobs.insert(("build", 3));
+        jobs.insert(("deploy", 5));
+        jobs.insert(("test", 1));
+
+        assert_eq!(jobs.pop(), Some(("test", 1)));
+        assert!(DaryHeap::<i32, 3>::new().into_sorted_vec().is_empty());
+
+        let mut heap = DaryHeap::<_, 2>::with_capacity(16);
+        assert!(heap.capacity() >= 16);
+        assert_eq!(heap.peek(), None);
+        assert!(heap.peek_mut().is_none());
+
+        heap.extend(vec![5, 3, 4, 9, -1, 0]);
+        assert_eq!(heap.len(), 6);
+        assert_eq!(heap.peek(), Some(&-1));
+
+        *heap.peek_mut().unwrap() = 6;
+        assert_eq!(heap.peek(), Some(&0));
+        assert_eq!(PeekMut::pop(heap.peek_mut().unwrap()), 0);
+        assert_eq!(heap.len(), 5);
+
+        heap.retain(|&x| x != 4);
+        let mut other = DaryHeap::from_vec((10..100).rev().collect());
+        heap.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(heap.len(), 94);
+
+        let mut drain = heap.drain_sorted();
+        assert_eq!(drain.len(), 94);
+        assert_eq!(drain.by_ref().take(5).collect::<Vec<_>>(), vec![3, 5, 6, 9, 10]);
+        drop(drain);
+        assert!(heap.is_empty());
+
+        heap.extend((0..1000).rev());
+        heap.extend(vec![-1, -2]);
+        heap.retain(|&x| x % 2 == 0);
+        let sorted = heap.into_sorted_vec();
+        assert_eq!(sorted[0], -2);
+        assert!(sorted.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(sorted.len(), 501);
+    }
+}
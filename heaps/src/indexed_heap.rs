@@ -0,0 +1,219 @@
+use crate::compare::*;
+use crate::dary_heap::{bubble_up_at, trickle_down_at};
+
+// marks the slots whose elements have been removed
+const REMOVED : usize = usize::MAX;
+
+// slots are reused after removal, so a handle also carries the generation of its slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Handle {
+    slot : usize,
+    generation : usize
+}
+
+#[derive(Debug)]
+struct Entry<T> {
+    value : T,
+    slot : usize
+}
+
+#[derive(Debug)]
+struct Slot {
+    pos : usize, // index of the element in src
+    generation : usize // bumped whenever the element leaves
+}
+
+#[derive(Debug)]
+pub struct IndexedHeap<T, C : Compare<T> = MinOrder> {
+    src : Vec<Entry<T>>,
+    slots : Vec<Slot>,
+    free : Vec<usize>,
+    cmp : C
+}
+
+impl<T : Ord> IndexedHeap<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+}
+
+impl<T, C : Compare<T>> IndexedHeap<T, C> {
+    pub fn with_comparator(cmp : C) -> Self {
+        Self { src : Vec::new(), slots : Vec::new(), free : Vec::new(), cmp }
+    }
+
+    pub fn len(&self) -> usize {
+        self.src.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.src.is_empty()
+    }
+
+    // restores the heap property around src[i] in whichever direction it is broken
+    fn fix(&mut self, i : usize) {
+        let cmp = &self.cmp;
+        let slots = &mut self.slots;
+        let mut swap = |src : &mut [Entry<T>], i : usize, j : usize| {
+            src.swap(i, j);
+            slots[src[i].slot].pos = i;
+            slots[src[j].slot].pos = j;
+        };
+
+        let i = bubble_up_at::<2, _, _, _>(&mut self.src, i, |a, b| cmp.less(&a.value, &b.value), &mut swap);
+        trickle_down_at::<2, _, _, _>(&mut self.src, i, |a, b| cmp.less(&a.value, &b.value), &mut swap);
+    }
+
+    fn handle(&self, slot : usize) -> Handle {
+        Handle { slot, generation : self.slots[slot].generation }
+    }
+
+    pub fn insert(&mut self, x : T) -> Handle {
+        let i = self.src.len();
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot].pos = i;
+                slot
+            },
+            None => {
+                self.slots.push(Slot { pos : i, generation : 0 });
+                self.slots.len() - 1
+            }
+        };
+        self.src.push(Entry { value : x, slot });
+
+        self.fix(i);
+
+        self.handle(slot)
+    }
+
+    pub fn contains(&self, h : Handle) -> bool {
+        self.slots.get(h.slot).map(|s| s.pos != REMOVED && s.generation == h.generation).unwrap_or(false)
+    }
+
+    pub fn get(&self, h : Handle) -> Option<&T> {
+        if self.contains(h) { Some(&self.src[self.slots[h.slot].pos].value) } else { None }
+    }
+
+    pub fn peek(&self) -> Option<(Handle, &T)> {
+        self.src.first().map(|e| (self.handle(e.slot), &e.value))
+    }
+
+    fn remove_at(&mut self, i : usize) -> (Handle, T) {
+        let last = self.src.len() - 1;
+        self.src.swap(i, last);
+
+        let entry = self.src.pop().unwrap();
+        let h = self.handle(entry.slot);
+        let slot = &mut self.slots[entry.slot];
+        slot.pos = REMOVED;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(entry.slot);
+
+        if i < self.src.len() {
+            self.slots[self.src[i].slot].pos = i;
+            self.fix(i);
+        }
+
+        (h, entry.value)
+    }
+
+    pub fn pop(&mut self) -> Option<(Handle, T)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        Some(self.remove_at(0))
+    }
+
+    pub fn remove(&mut self, h : Handle) -> Option<T> {
+        if !self.contains(h) {
+            return None;
+        }
+
+        Some(self.remove_at(self.slots[h.slot].pos).1)
+    }
+
+    // replaces the element of h with x and returns the old one
+    pub fn change_key(&mut self, h : Handle, x : T) -> Option<T> {
+        if !self.contains(h) {
+            return None;
+        }
+
+        let i = self.slots[h.slot].pos;
+        let old = std::mem::replace(&mut self.src[i].value, x);
+        self.fix(i);
+
+        Some(old)
+    }
+
+    // change_key towards the top, i.e. x must not come after the current element w.r.t. the comparator
+    pub fn decrease_key(&mut self, h : Handle, x : T) -> Option<T> {
+        debug_assert!(self.get(h).map(|old| !self.cmp.less(old, &x)).unwrap_or(true), "decrease_key would move the element down");
+        self.change_key(h, x)
+    }
+
+    // change_key away from the top, i.e. x must not come before the current element w.r.t. the comparator
+    pub fn increase_key(&mut self, h : Handle, x : T) -> Option<T> {
+        debug_assert!(self.get(h).map(|old| !self.cmp.less(&x, old)).unwrap_or(true), "increase_key would move the element up");
+        self.change_key(h, x)
+    }
+}
+
+impl<T, C : Compare<T> + Default> Default for IndexedHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexed_heap_works() {
+        let mut heap = IndexedHeap::new();
+
+        let handles : Vec<_> = vec![5, 3, 4, 9, -1, 0].into_iter().map(|x| heap.insert(x)).collect();
+
+        assert_eq!(heap.len(), 6);
+        assert_eq!(heap.peek(), Some((handles[4], &-1)));
+
+        assert_eq!(heap.decrease_key(handles[3], -5), Some(9));
+        assert_eq!(heap.peek(), Some((handles[3], &-5)));
+
+        assert_eq!(heap.increase_key(handles[3], 10), Some(-5));
+        assert_eq!(heap.get(handles[3]), Some(&10));
+        assert_eq!(heap.peek(), Some((handles[4], &-1)));
+
+        assert_eq!(heap.remove(handles[1]), Some(3));
+        assert!(!heap.contains(handles[1]));
+        assert_eq!(heap.remove(handles[1]), None);
+        assert_eq!(heap.decrease_key(handles[1], 0), None);
+
+        assert_eq!(heap.pop(), Some((handles[4], -1)));
+        assert_eq!(heap.pop(), Some((handles[5], 0)));
+        assert_eq!(heap.pop(), Some((handles[2], 4)));
+        assert_eq!(heap.pop(), Some((handles[0], 5)));
+        assert_eq!(heap.pop(), Some((handles[3], 10)));
+        assert_eq!(heap.pop(), None);
+        assert!(!heap.contains(handles[0]));
+
+        // removed slots are reused, and the handles of their old elements stay dead
+        let c = heap.insert(7);
+        assert!(handles.iter().all(|&h| h != c && !heap.contains(h)));
+        assert_eq!(heap.get(handles[0]), None);
+        assert_eq!(heap.change_key(handles[0], 1), None);
+        assert_eq!(heap.get(c), Some(&7));
+        assert_eq!(heap.slots.len(), 6);
+
+        // the directions follow the comparator, so a greater value is a decrease in a max-heap
+        let mut max_heap = IndexedHeap::<_, MaxOrder>::default();
+        let a = max_heap.insert(1);
+        let b = max_heap.insert(2);
+        max_heap.decrease_key(a, 3);
+
+        assert_eq!(max_heap.pop(), Some((a, 3)));
+        assert_eq!(max_heap.pop(), Some((b, 2)));
+    }
+}
@@ -0,0 +1,89 @@
+use std::cmp::Ordering;
+use crate::compare::*;
+use crate::binary_heap::BinaryHeap;
+use crate::dary_heap::PeekMut;
+
+#[derive(Debug)]
+struct Head<T> {
+    value : T,
+    src : usize // index of the iterator the value came from
+}
+
+// breaks ties by the index of the iterators, so that the merge is stable
+#[derive(Debug)]
+struct HeadOrder<C>(C);
+
+impl<T, C : Compare<T>> Compare<Head<T>> for HeadOrder<C> {
+    fn compare(&self, a : &Head<T>, b : &Head<T>) -> Ordering {
+        self.0.compare(&a.value, &b.value).then(a.src.cmp(&b.src))
+    }
+}
+
+// merges iterators sorted w.r.t. the comparator, pulling only one element ahead from each
+pub struct KWayMerge<I : Iterator, C : Compare<I::Item> = MinOrder> {
+    iters : Vec<I>,
+    heap : BinaryHeap<Head<I::Item>, HeadOrder<C>>
+}
+
+impl<I : Iterator> KWayMerge<I> where I::Item : Ord {
+    pub fn new<J : IntoIterator<Item = I>>(iters : J) -> Self {
+        Self::with_comparator(iters, MinOrder)
+    }
+}
+
+impl<I : Iterator, C : Compare<I::Item>> KWayMerge<I, C> {
+    pub fn with_comparator<J : IntoIterator<Item = I>>(iters : J, cmp : C) -> Self {
+        let mut iters : Vec<I> = iters.into_iter().collect();
+        let heads = iters.iter_mut().enumerate()
+            .filter_map(|(src, it)| it.next().map(|value| Head { value, src }))
+            .collect();
+
+        Self { iters, heap : BinaryHeap::from_vec_with(heads, HeadOrder(cmp)) }
+    }
+}
+
+impl<I : Iterator, C : Compare<I::Item>> Iterator for KWayMerge<I, C> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let mut head = self.heap.peek_mut()?;
+
+        match self.iters[head.src].next() {
+            Some(value) => Some(std::mem::replace(&mut head.value, value)),
+            None => Some(PeekMut::pop(head).value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iters.iter().fold((self.heap.len(), Some(self.heap.len())), |(lo, hi), it| {
+            let (l, h) = it.size_hint();
+            (lo.saturating_add(l), hi.and_then(|hi| h.and_then(|h| hi.checked_add(h))))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn k_way_merge_works() {
+        let runs = vec![vec![1, 4, 7, 10], vec![], vec![2, 3, 8], vec![0, 5, 6, 9, 11]];
+
+        let merge = KWayMerge::new(runs.into_iter().map(|r| r.into_iter()));
+        assert_eq!(merge.size_hint(), (12, Some(12)));
+        assert_eq!(merge.collect::<Vec<_>>(), (0..12).collect::<Vec<_>>());
+
+        let desc = vec![(5..10).rev(), (0..7).rev()];
+        let merged : Vec<_> = KWayMerge::with_comparator(desc, MaxOrder).collect();
+        assert_eq!(merged, vec![9, 8, 7, 6, 6, 5, 5, 4, 3, 2, 1, 0]);
+
+        let a = vec![(1, 'a'), (2, 'a'), (2, 'b')];
+        let b = vec![(1, 'c'), (2, 'c')];
+        let merged : Vec<_> = KWayMerge::with_comparator(vec![a.into_iter(), b.into_iter()], KeyOrder(|p : &(i32, char)| p.0)).collect();
+        assert_eq!(merged, vec![(1, 'a'), (1, 'c'), (2, 'a'), (2, 'b'), (2, 'c')]);
+
+        let infinite = vec![(0..).step_by(2), (1..).step_by(2)];
+        assert_eq!(KWayMerge::new(infinite).take(5).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+    }
+}
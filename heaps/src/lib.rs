@@ -1,3 +1,19 @@
+mod compare;
+mod dary_heap;
 mod binary_heap;
+mod indexed_heap;
+mod meldable_heap;
+mod pairing_heap;
+mod min_max_heap;
+mod top_k;
+mod k_way_merge;
 
-pub use binary_heap::*;
\ No newline at end of file
+pub use compare::*;
+pub use dary_heap::*;
+pub use binary_heap::*;
+pub use indexed_heap::*;
+pub use meldable_heap::*;
+pub use pairing_heap::*;
+pub use min_max_heap::*;
+pub use top_k::*;
+pub use k_way_merge::*;
\ No newline at end of file
@@ -0,0 +1,310 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::ptr;
+use std::rc::{Rc, Weak};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::compare::*;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+#[derive(Debug)]
+struct Node<T> {
+    value : T,
+    left : Link<T>,
+    right : Link<T>,
+    parent : *mut Node<T>,
+    token : Rc<Token<T>>
+}
+
+// the heap a node was inserted into, which forwards to the heap it was melded into
+#[derive(Debug, Default)]
+struct Owner {
+    next : RefCell<Option<Rc<Owner>>>
+}
+
+// the owner of o after all the melds, shortening the chain of forwards on the way
+fn current(o : &Rc<Owner>) -> Rc<Owner> {
+    let mut last = o.clone();
+    loop {
+        let next = last.next.borrow().clone();
+        match next {
+            Some(u) => last = u,
+            None => break
+        }
+    }
+
+    let mut u = o.clone();
+    while !Rc::ptr_eq(&u, &last) {
+        u = u.next.replace(Some(last.clone())).unwrap();
+    }
+
+    last
+}
+
+// lives exactly as long as its node, so that handles notice when the node is removed
+#[derive(Debug)]
+struct Token<T> {
+    node : *mut Node<T>,
+    owner : Rc<Owner>
+}
+
+// points an element of a MeldableHeap, and stops matching anything once the element is removed
+pub struct MeldableHandle<T>(Weak<Token<T>>);
+
+impl<T> Clone for MeldableHandle<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> PartialEq for MeldableHandle<T> {
+    fn eq(&self, other : &Self) -> bool {
+        self.0.ptr_eq(&other.0)
+    }
+}
+impl<T> Eq for MeldableHandle<T> {}
+
+impl<T> fmt::Debug for MeldableHandle<T> {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MeldableHandle({:p})", self.0.as_ptr())
+    }
+}
+
+#[derive(Debug)]
+pub struct MeldableHeap<T, C : Compare<T> = MinOrder> {
+    root : Link<T>,
+    n : usize,
+    cmp : C,
+    rng : StdRng,
+    owner : Rc<Owner>
+}
+
+impl<T : Ord> MeldableHeap<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+
+    pub fn with_seed(seed : u64) -> Self {
+        Self::with_comparator_and_seed(MinOrder, seed)
+    }
+}
+
+impl<T, C : Compare<T>> MeldableHeap<T, C> {
+    pub fn with_comparator(cmp : C) -> Self {
+        Self::with_rng(cmp, StdRng::from_entropy())
+    }
+
+    pub fn with_comparator_and_seed(cmp : C, seed : u64) -> Self {
+        Self::with_rng(cmp, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(cmp : C, rng : StdRng) -> Self {
+        Self { root : None, n : 0, cmp, rng, owner : Rc::default() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|r| &r.value)
+    }
+
+    // merges the heaps a and b into the empty link dst under parent, walking down a random path instead of recursing
+    unsafe fn merge_into(cmp : &C, rng : &mut StdRng, dst : *mut Link<T>, parent : *mut Node<T>, a : Link<T>, b : Link<T>) {
+        let (mut dst, mut parent) = (dst, parent);
+        let (mut a, mut b) = (a, b);
+
+        loop {
+            let (mut top, other) = match (a, b) {
+                (None, h) | (h, None) => {
+                    *dst = h;
+                    if let Some(h) = (*dst).as_mut() {
+                        h.parent = parent;
+                    }
+                    return;
+                },
+                (Some(x), Some(y)) => if cmp.less(&y.value, &x.value) { (y, x) } else { (x, y) }
+            };
+
+            // top stays above, and other goes into one of its subtrees
+            let left = rng.gen::<bool>();
+            a = if left { top.left.take() } else { top.right.take() };
+            b = Some(other);
+
+            top.parent = parent;
+            *dst = Some(top);
+            parent = (*dst).as_mut().unwrap().as_mut();
+            dst = if left { &mut (*parent).left } else { &mut (*parent).right };
+        }
+    }
+
+    pub fn insert(&mut self, x : T) -> MeldableHandle<T> {
+        let token = Rc::new(Token { node : ptr::null_mut(), owner : self.owner.clone() });
+        let mut node = Box::new(Node { value : x, left : None, right : None, parent : ptr::null_mut(), token });
+
+        // the token is not shared yet, so it can still learn the address of its node
+        let node_ptr : *mut Node<T> = node.as_mut();
+        Rc::get_mut(&mut node.token).unwrap().node = node_ptr;
+        let handle = MeldableHandle(Rc::downgrade(&node.token));
+
+        let root = self.root.take();
+        unsafe { Self::merge_into(&self.cmp, &mut self.rng, &mut self.root, ptr::null_mut(), Some(node), root) };
+        self.n += 1;
+
+        handle
+    }
+
+    // the node of h, when it is still in this heap
+    fn node_of(&self, h : &MeldableHandle<T>) -> Option<*mut Node<T>> {
+        let token = h.0.upgrade()?;
+        if Rc::ptr_eq(&current(&token.owner), &self.owner) { Some(token.node) } else { None }
+    }
+
+    pub fn contains(&self, h : &MeldableHandle<T>) -> bool {
+        self.node_of(h).is_some()
+    }
+
+    pub fn get(&self, h : &MeldableHandle<T>) -> Option<&T> {
+        self.node_of(h).map(|u| unsafe { &(*u).value })
+    }
+
+    // replaces u by the merge of its children, whose elements are not above the parent of u
+    unsafe fn remove_node(&mut self, u : *mut Node<T>) -> T {
+        let parent = (*u).parent;
+        let link : *mut Link<T> = if parent.is_null() {
+            &mut self.root
+        }
+        else if (*parent).left.as_deref().map(|l| ptr::eq(l, u)).unwrap_or(false) {
+            &mut (*parent).left
+        }
+        else {
+            &mut (*parent).right
+        };
+
+        let mut node = (*link).take().unwrap();
+        Self::merge_into(&self.cmp, &mut self.rng, link, parent, node.left.take(), node.right.take());
+        self.n -= 1;
+
+        node.value
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let r : *mut Node<T> = self.root.as_deref_mut()?;
+        unsafe { Some(self.remove_node(r)) }
+    }
+
+    pub fn remove(&mut self, h : &MeldableHandle<T>) -> Option<T> {
+        let u = self.node_of(h)?;
+        unsafe { Some(self.remove_node(u)) }
+    }
+
+    // moves all elements of other into self in expected O(log n) time, and the handles of other now point into self
+    pub fn meld(&mut self, other : Self) {
+        let mut other = other;
+        *other.owner.next.borrow_mut() = Some(self.owner.clone());
+
+        let root = self.root.take();
+        unsafe { Self::merge_into(&self.cmp, &mut self.rng, &mut self.root, ptr::null_mut(), root, other.root.take()) };
+        self.n += other.n;
+    }
+}
+
+// drops the nodes one by one, since the recursive drop of the boxes overflows the stack on long paths
+impl<T, C : Compare<T>> Drop for MeldableHeap<T, C> {
+    fn drop(&mut self) {
+        let mut stack : Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut u) = stack.pop() {
+            stack.extend(u.left.take());
+            stack.extend(u.right.take());
+        }
+    }
+}
+
+impl<T, C : Compare<T> + Default> Default for MeldableHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meldable_heap_works() {
+        let mut heap = MeldableHeap::with_seed(1);
+
+        let handles : Vec<_> = vec![5, 3, 4, 9, -1, 0, 7].into_iter().map(|x| heap.insert(x)).collect();
+
+        assert_eq!(heap.len(), 7);
+        assert_eq!(heap.peek(), Some(&-1));
+
+        assert_eq!(heap.remove(&handles[1]), Some(3));
+        assert_eq!(heap.remove(&handles[4]), Some(-1));
+        assert_eq!(heap.remove(&handles[4]), None);
+        assert_eq!(heap.peek(), Some(&0));
+
+        // a new element at the same address still does not match the old handle
+        let five = heap.insert(5);
+        assert!(!heap.contains(&handles[4]));
+        assert_eq!(heap.get(&five), Some(&5));
+        assert_eq!(heap.remove(&five), Some(5));
+
+        let mut other = MeldableHeap::with_seed(2);
+        other.insert(6);
+        let two = other.insert(2);
+        other.insert(8);
+
+        // handles only match the heap holding their element
+        assert!(!heap.contains(&two));
+        assert_eq!(heap.remove(&two), None);
+        assert!(!other.contains(&handles[0]));
+
+        heap.meld(other);
+        assert_eq!(heap.len(), 8);
+
+        assert_eq!(heap.get(&two), Some(&2));
+        assert_eq!(heap.remove(&two), Some(2));
+        assert_eq!(heap.remove(&handles[3]), Some(9));
+
+        // the handles of a heap melded into one that is melded again follow both melds
+        let mut first = MeldableHeap::with_seed(5);
+        let one = first.insert(1);
+        let mut second = MeldableHeap::with_seed(6);
+        second.meld(first);
+        heap.meld(second);
+        assert_eq!(heap.remove(&one), Some(1));
+
+        assert_eq!(heap.pop(), Some(0));
+        assert_eq!(heap.pop(), Some(4));
+        assert_eq!(heap.pop(), Some(5));
+        assert_eq!(heap.pop(), Some(6));
+        assert_eq!(heap.pop(), Some(7));
+        assert_eq!(heap.pop(), Some(8));
+        assert_eq!(heap.pop(), None);
+        assert!(heap.is_empty());
+
+        let mut max_heap = MeldableHeap::with_comparator_and_seed(MaxOrder, 3);
+        for i in 0..100 {
+            max_heap.insert((i * 37) % 100);
+        }
+        for i in (0..100).rev() {
+            assert_eq!(max_heap.pop(), Some(i));
+        }
+
+        // a long path must neither overflow the stack in merge nor in drop
+        let mut path = MeldableHeap::with_seed(4);
+        for i in (0..200_000).rev() {
+            path.insert(i);
+        }
+        assert_eq!(path.pop(), Some(0));
+        assert_eq!(path.pop(), Some(1));
+        drop(path);
+    }
+}
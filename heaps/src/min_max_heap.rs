@@ -0,0 +1,248 @@
+use crate::compare::*;
+use crate::dary_heap::{child, parent};
+
+// the nodes on even depths are less than their descendants, and the ones on odd depths are greater
+#[derive(Debug)]
+pub struct MinMaxHeap<T, C : Compare<T> = MinOrder> {
+    src : Vec<T>,
+    cmp : C
+}
+
+fn is_min_level(idx : usize) -> bool {
+    (usize::BITS - (idx + 1).leading_zeros()) % 2 == 1
+}
+
+impl<T : Ord> MinMaxHeap<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+
+    pub fn from_vec(v : Vec<T>) -> Self {
+        Self::from_vec_with(v, MinOrder)
+    }
+}
+
+impl<T, C : Compare<T>> MinMaxHeap<T, C> {
+    pub fn with_comparator(cmp : C) -> Self {
+        Self { src : Vec::new(), cmp }
+    }
+
+    pub fn from_vec_with(v : Vec<T>, cmp : C) -> Self {
+        let mut heap = Self { src : v, cmp };
+
+        for i in (0..(heap.src.len() / 2)).rev() {
+            heap.trickle_down(i);
+        }
+
+        heap
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.src
+    }
+
+    pub fn len(&self) -> usize {
+        self.src.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.src.is_empty()
+    }
+
+    // whether src[i] should be nearer to the root than src[j] on a min level (max == false) or on a max level
+    fn before(&self, i : usize, j : usize, max : bool) -> bool {
+        if max { self.cmp.less(&self.src[j], &self.src[i]) } else { self.cmp.less(&self.src[i], &self.src[j]) }
+    }
+
+    fn max_index(&self) -> Option<usize> {
+        match self.src.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => Some(if self.cmp.less(&self.src[1], &self.src[2]) { 2 } else { 1 })
+        }
+    }
+
+    fn bubble_up_grand(&mut self, i : usize, max : bool) {
+        let mut i = i;
+        while i > 2 {
+            let g = parent::<2>(parent::<2>(i));
+            if !self.before(i, g, max) {
+                break;
+            }
+
+            self.src.swap(i, g);
+            i = g;
+        }
+    }
+
+    fn bubble_up(&mut self, i : usize) {
+        if i == 0 {
+            return;
+        }
+
+        let p = parent::<2>(i);
+        let max = !is_min_level(i);
+        if self.before(p, i, max) {
+            self.src.swap(i, p);
+            self.bubble_up_grand(p, !max);
+        }
+        else {
+            self.bubble_up_grand(i, max);
+        }
+    }
+
+    fn trickle_down(&mut self, i : usize) {
+        let max = !is_min_level(i);
+        let n = self.src.len();
+
+        let mut i = i;
+        loop {
+            // the most extreme one among the children and grandchildren
+            let mut m = i;
+            for c in (0..2).map(|k| child::<2>(i, k)).filter(|&c| c < n) {
+                for d in std::iter::once(c).chain((0..2).map(|k| child::<2>(c, k))).filter(|&d| d < n) {
+                    if self.before(d, m, max) {
+                        m = d;
+                    }
+                }
+            }
+            if m == i {
+                break;
+            }
+
+            self.src.swap(i, m);
+            if parent::<2>(m) == i {
+                break;
+            }
+
+            let p = parent::<2>(m);
+            if self.before(p, m, max) {
+                self.src.swap(p, m);
+            }
+            i = m;
+        }
+    }
+
+    pub fn push(&mut self, x : T) {
+        self.src.push(x);
+        self.bubble_up(self.src.len() - 1);
+    }
+
+    pub fn peek_min(&self) -> Option<&T> {
+        self.src.first()
+    }
+
+    pub fn peek_max(&self) -> Option<&T> {
+        self.max_index().map(|i| &self.src[i])
+    }
+
+    fn remove_at(&mut self, i : usize) -> T {
+        let x = self.src.swap_remove(i);
+        if i < self.src.len() {
+            self.trickle_down(i);
+        }
+
+        x
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        if self.is_empty() { None } else { Some(self.remove_at(0)) }
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.max_index().map(|i| self.remove_at(i))
+    }
+
+    // pushes x and then pops the greatest element, which keeps the size of a bounded heap in O(log n) time
+    pub fn push_pop_max(&mut self, x : T) -> T {
+        let i = match self.max_index() {
+            Some(i) if self.cmp.less(&x, &self.src[i]) => i,
+            _ => return x
+        };
+
+        let old = std::mem::replace(&mut self.src[i], x);
+        if i > 0 && self.cmp.less(&self.src[i], &self.src[0]) {
+            self.src.swap(i, 0);
+        }
+        self.trickle_down(i);
+
+        old
+    }
+}
+
+impl<T, C : Compare<T> + Default> Default for MinMaxHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C : Compare<T> + Default> From<Vec<T>> for MinMaxHeap<T, C> {
+    fn from(v : Vec<T>) -> Self {
+        Self::from_vec_with(v, C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn min_max_heap_works() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut heap = MinMaxHeap::new();
+        let mut model : Vec<i32> = Vec::new();
+
+        for _ in 0..2000 {
+            match rng.gen_range(0, 4) {
+                0 | 1 => {
+                    let x = rng.gen_range(-100, 100);
+                    heap.push(x);
+                    let i = model.partition_point(|&y| y < x);
+                    model.insert(i, x);
+                },
+                2 => assert_eq!(heap.pop_min(), if model.is_empty() { None } else { Some(model.remove(0)) }),
+                _ => assert_eq!(heap.pop_max(), model.pop())
+            }
+
+            assert_eq!(heap.len(), model.len());
+            assert_eq!(heap.peek_min(), model.first());
+            assert_eq!(heap.peek_max(), model.last());
+        }
+
+        let v : Vec<i32> = (0..500).map(|_| rng.gen_range(-1000, 1000)).collect();
+        let mut heap = MinMaxHeap::from_vec(v.clone());
+        let mut model = v;
+        model.sort();
+
+        for _ in 0..500 {
+            let x = rng.gen_range(-1000, 1000);
+            let i = model.partition_point(|&y| y < x);
+            model.insert(i, x);
+            assert_eq!(heap.push_pop_max(x), model.pop().unwrap());
+            assert_eq!(heap.peek_min(), model.first());
+            assert_eq!(heap.peek_max(), model.last());
+        }
+        while !model.is_empty() {
+            assert_eq!(heap.pop_min(), Some(model.remove(0)));
+            assert_eq!(heap.pop_max(), model.pop());
+        }
+        assert!(heap.is_empty());
+
+        let mut max_first = MinMaxHeap::with_comparator(MaxOrder);
+        for x in [3, 1, 4, 1, 5] {
+            max_first.push(x);
+        }
+        assert_eq!(max_first.pop_min(), Some(5));
+        assert_eq!(max_first.pop_max(), Some(1));
+        assert_eq!(max_first.push_pop_max(0), 0);
+        assert_eq!(max_first.push_pop_max(9), 1);
+        assert_eq!(max_first.peek_min(), Some(&9));
+
+        let mut from_vec = MinMaxHeap::<_, MaxOrder>::from(vec![2, 7, 1, 8]);
+        assert_eq!(from_vec.pop_min(), Some(8));
+        assert_eq!(from_vec.pop_max(), Some(1));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::compare::*;
+
+// index of the nodes is used as the address
+const NIL : usize = usize::MAX;
+
+#[derive(Debug)]
+struct Node<T> {
+    value : Option<T>, // None while the slot is free
+    child : usize,
+    next : usize,
+    prev : usize, // parent if this is the first child, otherwise the previous sibling
+    generation : usize // bumped whenever the slot is freed
+}
+
+// points an element of a PairingHeap, and stops matching anything once the element is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PairingHandle {
+    slot : usize,
+    generation : usize
+}
+
+#[derive(Debug)]
+pub struct PairingHeap<T, C : Compare<T> = MinOrder> {
+    nodes : Vec<Node<T>>,
+    free : Vec<usize>,
+    root : usize,
+    n : usize,
+    cmp : C
+}
+
+impl<T : Ord> PairingHeap<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(MinOrder)
+    }
+}
+
+impl<T, C : Compare<T>> PairingHeap<T, C> {
+    pub fn with_comparator(cmp : C) -> Self {
+        Self { nodes : Vec::new(), free : Vec::new(), root : NIL, n : 0, cmp }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    fn value(&self, u : usize) -> &T {
+        self.nodes[u].value.as_ref().unwrap()
+    }
+
+    // makes the greater root a child of the other, and returns the new root
+    fn link(&mut self, a : usize, b : usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+
+        let (a, b) = if self.cmp.less(self.value(b), self.value(a)) { (b, a) } else { (a, b) };
+
+        let first = self.nodes[a].child;
+        self.nodes[b].next = first;
+        self.nodes[b].prev = a;
+        if first != NIL {
+            self.nodes[first].prev = b;
+        }
+        self.nodes[a].child = b;
+
+        a
+    }
+
+    // merges the siblings starting from first by pairing them left to right and then folding right to left
+    fn two_pass(&mut self, first : usize) -> usize {
+        let mut trees = Vec::new();
+        let mut u = first;
+        while u != NIL {
+            let next = self.nodes[u].next;
+            self.nodes[u].next = NIL;
+            self.nodes[u].prev = NIL;
+            trees.push(u);
+            u = next;
+        }
+
+        let mut paired = Vec::with_capacity(trees.len().div_ceil(2));
+        for pair in trees.chunks(2) {
+            let b = if pair.len() == 2 { pair[1] } else { NIL };
+            paired.push(self.link(pair[0], b));
+        }
+
+        paired.into_iter().rev().fold(NIL, |acc, t| self.link(t, acc))
+    }
+
+    // separates the subtree of u (u != root) from its parent and siblings
+    fn cut(&mut self, u : usize) {
+        let prev = self.nodes[u].prev;
+        let next = self.nodes[u].next;
+
+        if self.nodes[prev].child == u {
+            self.nodes[prev].child = next;
+        }
+        else {
+            self.nodes[prev].next = next;
+        }
+        if next != NIL {
+            self.nodes[next].prev = prev;
+        }
+
+        self.nodes[u].prev = NIL;
+        self.nodes[u].next = NIL;
+    }
+
+    // takes u out of the heap, keeping its value in the arena
+    fn detach(&mut self, u : usize) {
+        let child = self.nodes[u].child;
+        self.nodes[u].child = NIL;
+
+        if u == self.root {
+            self.root = self.two_pass(child);
+        }
+        else {
+            self.cut(u);
+            let sub = self.two_pass(child);
+            self.root = self.link(self.root, sub);
+        }
+    }
+
+    // takes the value out of the detached node u, and frees its slot
+    fn release(&mut self, u : usize) -> T {
+        let node = &mut self.nodes[u];
+        node.generation = node.generation.wrapping_add(1);
+        self.free.push(u);
+        self.n -= 1;
+
+        node.value.take().unwrap()
+    }
+
+    fn handle(&self, slot : usize) -> PairingHandle {
+        PairingHandle { slot, generation : self.nodes[slot].generation }
+    }
+
+    pub fn insert(&mut self, x : T) -> PairingHandle {
+        let node = Node { value : Some(x), child : NIL, next : NIL, prev : NIL, generation : 0 };
+        let u = match self.free.pop() {
+            Some(u) => {
+                self.nodes[u] = Node { generation : self.nodes[u].generation, ..node };
+                u
+            },
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+
+        self.root = self.link(self.root, u);
+        self.n += 1;
+
+        self.handle(u)
+    }
+
+    pub fn contains(&self, h : PairingHandle) -> bool {
+        self.nodes.get(h.slot).map(|u| u.value.is_some() && u.generation == h.generation).unwrap_or(false)
+    }
+
+    pub fn get(&self, h : PairingHandle) -> Option<&T> {
+        if self.contains(h) { self.nodes[h.slot].value.as_ref() } else { None }
+    }
+
+    pub fn peek(&self) -> Option<(PairingHandle, &T)> {
+        if self.root == NIL { None } else { Some((self.handle(self.root), self.value(self.root))) }
+    }
+
+    pub fn pop(&mut self) -> Option<(PairingHandle, T)> {
+        if self.root == NIL {
+            return None;
+        }
+
+        let r = self.root;
+        let h = self.handle(r);
+        self.detach(r);
+
+        Some((h, self.release(r)))
+    }
+
+    pub fn remove(&mut self, h : PairingHandle) -> Option<T> {
+        if !self.contains(h) {
+            return None;
+        }
+
+        self.detach(h.slot);
+
+        Some(self.release(h.slot))
+    }
+
+    // replaces the element of h with x and returns the old one, in O(1) amortised time when x is not greater
+    pub fn decrease_key(&mut self, h : PairingHandle, x : T) -> Option<T> {
+        if !self.contains(h) {
+            return None;
+        }
+
+        let u = h.slot;
+        let increased = self.cmp.less(self.value(u), &x);
+        let old = self.nodes[u].value.replace(x);
+
+        if increased {
+            self.detach(u);
+            self.root = self.link(self.root, u);
+        }
+        else if u != self.root {
+            self.cut(u);
+            self.root = self.link(self.root, u);
+        }
+
+        old
+    }
+
+    // moves all elements of other into self, and returns the map from the handles of other into ones of self
+    pub fn meld(&mut self, other : Self) -> impl Fn(PairingHandle) -> PairingHandle {
+        let offset = self.nodes.len();
+        let shift = |u : usize| if u == NIL { NIL } else { u + offset };
+
+        self.nodes.extend(other.nodes.into_iter().map(|u| Node {
+            child : shift(u.child),
+            next : shift(u.next),
+            prev : shift(u.prev),
+            ..u
+        }));
+        self.free.extend(other.free.into_iter().map(shift));
+
+        self.root = self.link(self.root, shift(other.root));
+        self.n += other.n;
+
+        move |h : PairingHandle| PairingHandle { slot : h.slot + offset, ..h }
+    }
+}
+
+impl<T, C : Compare<T> + Default> Default for PairingHeap<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairing_heap_works() {
+        let mut heap = PairingHeap::new();
+
+        let handles : Vec<_> = vec![5, 3, 4, 9, -1, 0, 7].into_iter().map(|x| heap.insert(x)).collect();
+
+        assert_eq!(heap.len(), 7);
+        assert_eq!(heap.peek(), Some((handles[4], &-1)));
+        assert_eq!(heap.pop(), Some((handles[4], -1)));
+        assert!(!heap.contains(handles[4]));
+
+        assert_eq!(heap.decrease_key(handles[3], -2), Some(9));
+        assert_eq!(heap.peek(), Some((handles[3], &-2)));
+        assert_eq!(heap.decrease_key(handles[3], 10), Some(-2));
+        assert_eq!(heap.peek(), Some((handles[5], &0)));
+
+        assert_eq!(heap.remove(handles[2]), Some(4));
+        assert_eq!(heap.remove(handles[2]), None);
+        assert_eq!(heap.decrease_key(handles[4], 0), None);
+
+        // the freed slots are reused, but the old handles do not match the new elements
+        let reused : Vec<_> = (20..22).map(|x| heap.insert(x)).collect();
+        assert_eq!(heap.nodes.len(), 7);
+        assert!(!heap.contains(handles[2]) && !heap.contains(handles[4]));
+        assert_eq!(heap.get(handles[2]), None);
+        assert_eq!(reused.iter().map(|&h| heap.remove(h)).collect::<Vec<_>>(), vec![Some(20), Some(21)]);
+
+        let mut other = PairingHeap::new();
+        other.insert(6);
+        let one = other.insert(1);
+        other.insert(8);
+
+        let remap = heap.meld(other);
+        assert_eq!(heap.len(), 8);
+        assert_eq!(heap.decrease_key(remap(one), 2), Some(1));
+
+        let mut sorted = Vec::new();
+        while let Some((_, x)) = heap.pop() {
+            sorted.push(x);
+        }
+        assert_eq!(sorted, vec![0, 2, 3, 5, 6, 7, 8, 10]);
+
+        let mut max_heap = PairingHeap::with_comparator(MaxOrder);
+        for i in 0..100 {
+            max_heap.insert((i * 37) % 100);
+        }
+        for i in (0..100).rev() {
+            assert_eq!(max_heap.pop().map(|(_, x)| x), Some(i));
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::compare::*;
+use crate::binary_heap::BinaryHeap;
+
+// keeps the k least elements pushed so far, where the worst of them is on the top of the heap
+#[derive(Debug)]
+pub struct TopK<T, C : Compare<T> = MinOrder> {
+    heap : BinaryHeap<T, Reversed<C>>,
+    k : usize
+}
+
+impl<T : Ord> TopK<T> {
+    pub fn new(k : usize) -> Self {
+        Self::with_comparator(k, MinOrder)
+    }
+}
+
+impl<T, C : Compare<T>> TopK<T, C> {
+    pub fn with_comparator(k : usize, cmp : C) -> Self {
+        Self { heap : BinaryHeap::with_comparator_and_capacity(Reversed(cmp), k), k }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.k
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.heap.len() == self.k
+    }
+
+    // the element evicted next
+    pub fn peek_worst(&self) -> Option<&T> {
+        self.heap.peek()
+    }
+
+    // returns the element dropped out of the k best, which is x itself if it is not good enough
+    pub fn push(&mut self, x : T) -> Option<T> {
+        if !self.is_full() {
+            self.heap.insert(x);
+            return None;
+        }
+
+        let better = self.heap.peek().map(|worst| self.heap.comparator().less(worst, &x)).unwrap_or(false);
+        if !better {
+            return Some(x);
+        }
+
+        let mut worst = self.heap.peek_mut().unwrap();
+        Some(std::mem::replace(&mut *worst, x))
+    }
+
+    // the kept elements from the best to the worst
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut v = self.heap.into_sorted_vec();
+        v.reverse();
+        v
+    }
+}
+
+impl<T, C : Compare<T>> Extend<T> for TopK<T, C> {
+    fn extend<I : IntoIterator<Item = T>>(&mut self, iter : I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+// the k least elements of iter in the ascending order
+pub fn k_smallest<T : Ord, I : IntoIterator<Item = T>>(iter : I, k : usize) -> Vec<T> {
+    k_smallest_by(iter, k, MinOrder)
+}
+
+pub fn k_smallest_by<T, C : Compare<T>, I : IntoIterator<Item = T>>(iter : I, k : usize, cmp : C) -> Vec<T> {
+    let mut top = TopK::with_comparator(k, cmp);
+    top.extend(iter);
+    top.into_sorted_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_k_works() {
+        let mut top = TopK::new(3);
+
+        assert_eq!(top.push(5), None);
+        assert_eq!(top.push(3), None);
+        assert_eq!(top.push(9), None);
+        assert!(top.is_full());
+        assert_eq!(top.peek_worst(), Some(&9));
+
+        assert_eq!(top.push(4), Some(9));
+        assert_eq!(top.push(7), Some(7));
+        assert_eq!(top.push(-1), Some(5));
+        assert_eq!(top.len(), 3);
+        assert_eq!(top.into_sorted_vec(), vec![-1, 3, 4]);
+
+        let v : Vec<i32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        assert_eq!(k_smallest(v.iter().copied(), 5), vec![0, 1, 2, 3, 4]);
+        assert_eq!(k_smallest_by(v.iter().copied(), 3, MaxOrder), vec![999, 998, 997]);
+        assert_eq!(k_smallest(v.iter().copied(), 0), vec![]);
+        assert_eq!(k_smallest(vec![2, 1], 5), vec![1, 2]);
+
+        let words = vec!["pear", "fig", "banana", "kiwi", "apple"];
+        assert_eq!(k_smallest_by(words, 2, KeyOrder(|w : &&str| w.len())), vec!["fig", "pear"]);
+    }
+}
@@ -0,0 +1,73 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use lists::{AccessCount, MoveToFront, Reorder, SelfOrganizingList, Transpose};
+use trees::BSTree;
+
+const KEYS : usize = 1_000;
+const QUERIES : usize = 10_000;
+
+// samples keys in 0..n whose k-th most frequent one appears with probability proportional to 1/k^s
+fn zipf_queries(n : usize, s : f64, count : usize) -> Vec<u32> {
+    let mut cdf = Vec::with_capacity(n);
+    let mut sum = 0.0;
+    for k in 1..=n {
+        sum += 1.0 / (k as f64).powf(s);
+        cdf.push(sum);
+    }
+
+    // shuffle the ranks so that frequent keys are not inserted first
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut keys : Vec<u32> = (0..n as u32).collect();
+    for i in (1..n).rev() {
+        keys.swap(i, rng.gen_range(0, i + 1));
+    }
+
+    (0..count).map(|_| {
+        let r = rng.gen::<f64>() * sum;
+        let k = match cdf.binary_search_by(|c| c.partial_cmp(&r).unwrap()) { Ok(k) => k, Err(k) => k };
+        keys[k.min(n - 1)]
+    }).collect()
+}
+
+fn filled<R : Reorder>() -> SelfOrganizingList<u32, u32, R> {
+    let mut list = SelfOrganizingList::new();
+    for k in 0..KEYS as u32 {
+        list.insert(k, k);
+    }
+    list
+}
+
+fn zipf_lookup(c : &mut Criterion) {
+    let mut group = c.benchmark_group("zipf_lookup");
+    // lookups do not reorganize the tree, so it is built once; a permutation of the keys keeps it from degenerating into a path
+    let tree = BSTree::from_vec((0..KEYS as u32).map(|k| (k * 7919 % KEYS as u32, k)).collect());
+
+    for &s in [0.0, 1.0, 1.5].iter() {
+        let queries = zipf_queries(KEYS, s, QUERIES);
+
+        group.bench_with_input(BenchmarkId::new("MoveToFrontList", s), &queries, |b, queries| b.iter_batched_ref(
+            filled::<MoveToFront>,
+            |list| queries.iter().map(|q| *list.find(q).unwrap()).fold(0u32, u32::wrapping_add),
+            BatchSize::SmallInput
+        ));
+        group.bench_with_input(BenchmarkId::new("TransposeList", s), &queries, |b, queries| b.iter_batched_ref(
+            filled::<Transpose>,
+            |list| queries.iter().map(|q| *list.find(q).unwrap()).fold(0u32, u32::wrapping_add),
+            BatchSize::SmallInput
+        ));
+        group.bench_with_input(BenchmarkId::new("AccessCountList", s), &queries, |b, queries| b.iter_batched_ref(
+            filled::<AccessCount>,
+            |list| queries.iter().map(|q| *list.find(q).unwrap()).fold(0u32, u32::wrapping_add),
+            BatchSize::SmallInput
+        ));
+        group.bench_with_input(BenchmarkId::new("BSTree", s), &queries, |b, queries| b.iter(|| {
+            queries.iter().map(|q| *tree.get(q).unwrap()).fold(0u32, u32::wrapping_add)
+        }));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, zipf_lookup);
+criterion_main!(benches);
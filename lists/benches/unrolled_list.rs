@@ -0,0 +1,76 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use lists::{SEList, UnrolledList};
+
+const SIZES : [usize; 2] = [1_000, 10_000];
+
+fn random_indices(n : usize) -> Vec<usize> {
+    let mut rng = StdRng::seed_from_u64(0);
+    (0..n).map(|i| rng.gen_range(0, i + 1)).collect()
+}
+
+fn random_insert(c : &mut Criterion) {
+    let mut group = c.benchmark_group("random_insert");
+
+    for &n in SIZES.iter() {
+        let idx = random_indices(n);
+
+        group.bench_with_input(BenchmarkId::new("UnrolledList", n), &idx, |b, idx| b.iter(|| {
+            let mut list = UnrolledList::<u64>::new();
+            for (x, &i) in idx.iter().enumerate() {
+                list.add(i, x as u64);
+            }
+            list
+        }));
+        group.bench_with_input(BenchmarkId::new("SEList", n), &idx, |b, idx| b.iter(|| {
+            let mut list = SEList::<u64>::new();
+            for (x, &i) in idx.iter().enumerate() {
+                list.add(i, x as u64);
+            }
+            list
+        }));
+        group.bench_with_input(BenchmarkId::new("Vec", n), &idx, |b, idx| b.iter(|| {
+            let mut list = Vec::<u64>::new();
+            for (x, &i) in idx.iter().enumerate() {
+                list.insert(i, x as u64);
+            }
+            list
+        }));
+    }
+
+    group.finish();
+}
+
+fn random_get(c : &mut Criterion) {
+    let mut group = c.benchmark_group("random_get");
+
+    for &n in SIZES.iter() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let idx : Vec<usize> = (0..n).map(|_| rng.gen_range(0, n)).collect();
+
+        let mut unrolled = UnrolledList::<u64>::new();
+        let mut se = SEList::<u64>::new();
+        let mut vec = Vec::<u64>::new();
+        for x in 0..n as u64 {
+            unrolled.push_back(x);
+            se.push_back(x);
+            vec.push(x);
+        }
+
+        group.bench_with_input(BenchmarkId::new("UnrolledList", n), &idx, |b, idx| b.iter(|| {
+            idx.iter().map(|&i| *unrolled.get(i).unwrap()).sum::<u64>()
+        }));
+        group.bench_with_input(BenchmarkId::new("SEList", n), &idx, |b, idx| b.iter(|| {
+            idx.iter().map(|&i| *se.get(i).unwrap()).sum::<u64>()
+        }));
+        group.bench_with_input(BenchmarkId::new("Vec", n), &idx, |b, idx| b.iter(|| {
+            idx.iter().map(|&i| vec[i]).sum::<u64>()
+        }));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, random_insert, random_get);
+criterion_main!(benches);
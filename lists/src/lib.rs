@@ -1,5 +1,15 @@
 mod sl_list;
 mod se_list;
+mod skiplist_list;
+mod text_buffer;
+mod xor_list;
+mod unrolled_list;
+mod self_organizing_list;
 
 pub use sl_list::*;
 pub use se_list::*;
+pub use skiplist_list::*;
+pub use text_buffer::*;
+pub use xor_list::*;
+pub use unrolled_list::*;
+pub use self_organizing_list::*;
@@ -1,5 +1,6 @@
 use std::ptr;
 use std::mem;
+use std::marker::PhantomData;
 use arrays::ArrayDeque;
 
 type BoundedDeque<T> = ArrayDeque<T>;
@@ -205,7 +206,7 @@ impl<T : Default + Clone> SEList<T> {
         while cur != node {
             let prev = (*cur).prev;
 
-            while (*cur).block.size() <= self.block_size - 1 {
+            while (*cur).block.size() < self.block_size - 1 {
                 (*cur).block.push_front((*prev).block.pop_back().unwrap());
             }
 
@@ -241,21 +242,21 @@ impl<T : Default + Clone> SEList<T> {
         if r == self.block_size { // next b blocks are full
             unsafe {
                 self.spread(origin_node);
+                // the origin is still full, so the block after it (now b-1 elements) takes over its last element
+                node_ptr = (*origin_node).next.as_mut().unwrap().as_mut();
             }
         }
-        else {
-            if node_ptr == self.dummy.as_mut().prev && self.last_mut().block.size() == self.block_size { // come to last and last is full
-                self.push_back_node();
-                node_ptr = self.dummy.prev;
-            }
+        else if node_ptr == self.dummy.as_mut().prev && self.last_mut().block.size() == self.block_size { // come to last and last is full
+            self.push_back_node();
+            node_ptr = self.dummy.prev;
+        }
 
-            while node_ptr != origin_node {
-                unsafe {
-                    let cur = &mut *node_ptr;
-                    cur.block.push_front((*cur.prev).block.pop_back().unwrap());
-    
-                    node_ptr = cur.prev;
-                }
+        while node_ptr != origin_node {
+            unsafe {
+                let cur = &mut *node_ptr;
+                cur.block.push_front((*cur.prev).block.pop_back().unwrap());
+
+                node_ptr = cur.prev;
             }
         }
 
@@ -309,7 +310,7 @@ impl<T : Default + Clone> SEList<T> {
         let mut dist_node = origin_node;
         let mut r = 0;
         unsafe {
-            while r < self.block_size && (*dist_node).block.size() < self.block_size {
+            while r < self.block_size && (*dist_node).block.size() == self.block_size - 1 {
                 r += 1;
                 
                 if dist_node != self.dummy.as_mut().prev {
@@ -361,12 +362,89 @@ impl<T : Default + Clone> SEList<T> {
     pub fn size(&self) -> usize {
         self.n
     }
+
+    pub fn iter(&self) -> SEListIter<'_, T> {
+        self.iter_from(0)
+    }
+
+    // iterates over the elements at idx and after
+    pub fn iter_from(&self, idx : usize) -> SEListIter<'_, T> {
+        if idx >= self.n {
+            return SEListIter { node : ptr::null(), idx : 0, rem : 0, _marker : PhantomData };
+        }
+
+        let rem = self.n - idx;
+        let (node, idx) = self.get_location(idx);
+
+        SEListIter { node, idx, rem, _marker : PhantomData }
+    }
+}
+
+pub struct SEListIter<'a, T : Default + Clone> {
+    node : *const Node<T>,
+    idx : usize,
+    rem : usize,
+    _marker : PhantomData<&'a T>
+}
+
+impl<'a, T : Default + Clone> Iterator for SEListIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rem == 0 {
+            return None;
+        }
+
+        unsafe {
+            let mut node = &*self.node;
+            if self.idx == node.block.size() {
+                node = node.next.as_ref().unwrap().as_ref();
+                self.node = node;
+                self.idx = 0;
+            }
+
+            self.idx += 1;
+            self.rem -= 1;
+
+            node.block.get(self.idx - 1)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rem, Some(self.rem))
+    }
+}
+
+impl<'a, T : Default + Clone> IntoIterator for &'a SEList<T> {
+    type Item = &'a T;
+    type IntoIter = SEListIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // every block but the last holds b-1 to b+1 elements
+    fn check_blocks<T : Default + Clone>(list : &SEList<T>) {
+        let b = list.block_size;
+        let mut sizes = Vec::new();
+        let mut node = list.dummy.next.as_ref();
+        while let Some(u) = node {
+            sizes.push(u.block.size());
+            node = u.next.as_ref();
+        }
+
+        assert_eq!(sizes.iter().sum::<usize>(), list.size());
+        if let Some((last, rest)) = sizes.split_last() {
+            assert!(*last <= b + 1, "{:?}", sizes);
+            assert!(rest.iter().all(|&s| b - 1 <= s && s <= b + 1), "{:?}", sizes);
+        }
+    }
+
     #[test]
     fn se_list_works() {
         let mut list = SEList::<i32>::new();
@@ -414,5 +492,40 @@ mod tests {
         for i in 0..36 {
             assert_eq!(list.remove(0), Some(i));
         }
+
+        // inserting and removing in the middle spreads and gathers full blocks
+        let mut model = Vec::new();
+        for i in 0i32..100 {
+            list.add(model.len() / 2, i);
+            model.insert(model.len() / 2, i);
+            check_blocks(&list);
+        }
+        for (i, x) in model.iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+        for _ in 0..60 {
+            assert_eq!(list.remove(model.len() / 3), Some(model.remove(model.len() / 3)));
+            check_blocks(&list);
+        }
+        for (i, x) in model.iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+        while !model.is_empty() {
+            let i = (model.len() * 7 / 11) % model.len();
+            assert_eq!(list.remove(i), Some(model.remove(i)));
+            check_blocks(&list);
+        }
+        assert_eq!(list.size(), 0);
+
+        let mut model = Vec::new();
+        for i in 0i32..100 {
+            list.add(model.len() / 2, i);
+            model.insert(model.len() / 2, i);
+        }
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), model);
+        for _ in 0..60 {
+            assert_eq!(list.remove(model.len() / 3), Some(model.remove(model.len() / 3)));
+        }
+        assert_eq!(list.iter_from(10).cloned().collect::<Vec<_>>(), &model[10..]);
     }
 }
\ No newline at end of file
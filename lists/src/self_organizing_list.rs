@@ -0,0 +1,208 @@
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessStats {
+    pub searches : u64,
+    pub comparisons : u64
+}
+
+impl AccessStats {
+    // average number of keys compared per search
+    pub fn average_depth(&self) -> f64 {
+        if self.searches == 0 { 0.0 } else { self.comparisons as f64 / self.searches as f64 }
+    }
+}
+
+#[derive(Debug)]
+struct Entry<K, V> {
+    key : K,
+    value : V,
+    count : usize
+}
+
+// decides where the entry found at idx moves to, given the access count of each position
+pub trait Reorder {
+    fn target<F : Fn(usize) -> usize>(idx : usize, count_at : F) -> usize;
+}
+
+#[derive(Debug)]
+pub struct MoveToFront;
+
+#[derive(Debug)]
+pub struct Transpose;
+
+#[derive(Debug)]
+pub struct AccessCount;
+
+impl Reorder for MoveToFront {
+    fn target<F : Fn(usize) -> usize>(_idx : usize, _count_at : F) -> usize {
+        0
+    }
+}
+
+impl Reorder for Transpose {
+    fn target<F : Fn(usize) -> usize>(idx : usize, _count_at : F) -> usize {
+        idx.saturating_sub(1)
+    }
+}
+
+impl Reorder for AccessCount {
+    fn target<F : Fn(usize) -> usize>(idx : usize, count_at : F) -> usize {
+        let count = count_at(idx);
+
+        let mut i = idx;
+        while i > 0 && count_at(i - 1) < count {
+            i -= 1;
+        }
+
+        i
+    }
+}
+
+#[derive(Debug)]
+pub struct SelfOrganizingList<K : Eq, V, R : Reorder> {
+    entries : Vec<Entry<K, V>>,
+    stats : AccessStats,
+    _rule : PhantomData<R>
+}
+
+pub type MoveToFrontList<K, V> = SelfOrganizingList<K, V, MoveToFront>;
+pub type TransposeList<K, V> = SelfOrganizingList<K, V, Transpose>;
+pub type AccessCountList<K, V> = SelfOrganizingList<K, V, AccessCount>;
+
+impl<K : Eq, V, R : Reorder> SelfOrganizingList<K, V, R> {
+    pub fn new() -> Self {
+        Self { entries : Vec::new(), stats : AccessStats::default(), _rule : PhantomData }
+    }
+
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> AccessStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = AccessStats::default();
+    }
+
+    fn position(&self, key : &K) -> Option<usize> {
+        self.entries.iter().position(|e| e.key == *key)
+    }
+
+    // searches key with counting the comparisons, and restructures the list if found
+    fn access(&mut self, key : &K) -> Option<usize> {
+        let idx = self.position(key);
+
+        self.stats.searches += 1;
+        self.stats.comparisons += idx.map(|i| i + 1).unwrap_or(self.entries.len()) as u64;
+
+        idx.map(|i| {
+            self.entries[i].count += 1;
+
+            let entries = &self.entries;
+            let j = R::target(i, |k| entries[k].count);
+            self.entries[j..=i].rotate_right(1);
+
+            j
+        })
+    }
+
+    pub fn find(&mut self, key : &K) -> Option<&V> {
+        let idx = self.access(key)?;
+        Some(&self.entries[idx].value)
+    }
+
+    pub fn find_mut(&mut self, key : &K) -> Option<&mut V> {
+        let idx = self.access(key)?;
+        Some(&mut self.entries[idx].value)
+    }
+
+    // peeks the value without restructuring nor counting
+    pub fn get(&self, key : &K) -> Option<&V> {
+        self.position(key).map(|i| &self.entries[i].value)
+    }
+
+    // new keys are appended to the back, and existing keys keep their place
+    pub fn insert(&mut self, key : K, value : V) -> Option<V> {
+        if let Some(i) = self.position(&key) {
+            return Some(std::mem::replace(&mut self.entries[i].value, value));
+        }
+
+        self.entries.push(Entry { key, value, count : 0 });
+        None
+    }
+
+    pub fn remove(&mut self, key : &K) -> Option<V> {
+        let i = self.position(key)?;
+        Some(self.entries.remove(i).value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|e| (&e.key, &e.value))
+    }
+}
+
+impl<K : Eq, V, R : Reorder> Default for SelfOrganizingList<K, V, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys<R : Reorder>(list : &SelfOrganizingList<i32, i32, R>) -> Vec<i32> {
+        list.iter().map(|(k, _)| *k).collect()
+    }
+
+    #[test]
+    fn self_organizing_list_works() {
+        let mut mtf = MoveToFrontList::new();
+        let mut tr = TransposeList::new();
+        let mut cnt = AccessCountList::new();
+
+        for i in 0..5 {
+            assert_eq!(mtf.insert(i, i * 10), None);
+            tr.insert(i, i * 10);
+            cnt.insert(i, i * 10);
+        }
+
+        assert_eq!(mtf.find(&3), Some(&30));
+        assert_eq!(keys(&mtf), vec![3, 0, 1, 2, 4]);
+        assert_eq!(tr.find(&3), Some(&30));
+        assert_eq!(keys(&tr), vec![0, 1, 3, 2, 4]);
+        assert_eq!(cnt.find(&3), Some(&30));
+        assert_eq!(keys(&cnt), vec![3, 0, 1, 2, 4]);
+
+        assert_eq!(mtf.find(&4), Some(&40));
+        assert_eq!(keys(&mtf), vec![4, 3, 0, 1, 2]);
+        assert_eq!(tr.find(&4), Some(&40));
+        assert_eq!(keys(&tr), vec![0, 1, 3, 4, 2]);
+        cnt.find(&4);
+        cnt.find(&4);
+        assert_eq!(keys(&cnt), vec![4, 3, 0, 1, 2]);
+
+        assert_eq!(mtf.find(&7), None);
+        assert_eq!(mtf.stats(), AccessStats { searches : 3, comparisons : 4 + 5 + 5 });
+        assert_eq!(mtf.stats().average_depth(), 14.0 / 3.0);
+
+        assert_eq!(mtf.insert(0, 1), Some(0));
+        if let Some(v) = mtf.find_mut(&0) {
+            *v += 1;
+        }
+        assert_eq!(mtf.get(&0), Some(&2));
+        assert_eq!(mtf.remove(&0), Some(2));
+        assert_eq!(mtf.remove(&0), None);
+        assert_eq!(mtf.size(), 4);
+
+        mtf.reset_stats();
+        assert_eq!(mtf.stats().average_depth(), 0.0);
+    }
+}
@@ -0,0 +1,399 @@
+use std::ptr;
+use std::mem;
+use std::marker::PhantomData;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+const MAX_HEIGHT : usize = 33;
+
+struct Node<T> {
+    value : T,
+    next : Vec<*mut Self>,
+    length : Vec<usize>
+}
+
+impl<T> Node<T> {
+    fn new(value : T, height : usize) -> Self {
+        Self { value, next : vec![ptr::null_mut(); height + 1], length : vec![0; height + 1] }
+    }
+}
+
+pub struct SkiplistList<T : Default> {
+    sentinel : Box<Node<T>>,
+    h : usize,
+    n : usize,
+    rng : StdRng
+}
+
+impl<T : Default> SkiplistList<T> {
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    pub fn with_seed(seed : u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng : StdRng) -> Self {
+        Self { sentinel : Box::new(Node::new(T::default(), MAX_HEIGHT - 1)), h : 0, n : 0, rng }
+    }
+
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    fn pick_height(&mut self) -> usize {
+        self.rng.gen::<u32>().trailing_ones() as usize
+    }
+
+    // raises the height to h, where an edge to null has the number of the elements after it as its length
+    fn grow(&mut self, h : usize) {
+        for r in (self.h + 1)..=h {
+            self.sentinel.length[r] = self.n;
+        }
+
+        if h > self.h {
+            self.h = h;
+        }
+    }
+
+    // returns the node just before idx, where idx must satisfy idx < n
+    fn find_pred(&self, idx : usize) -> *mut Node<T> {
+        let mut u : *const Node<T> = self.sentinel.as_ref();
+        let mut j = 0;
+
+        for r in (0..=self.h).rev() {
+            let mut node = unsafe { &*u };
+            while !node.next[r].is_null() && j + node.length[r] <= idx {
+                j += node.length[r];
+                u = node.next[r];
+                node = unsafe { &*u };
+            }
+        }
+
+        u as *mut _
+    }
+
+    pub fn get(&self, idx : usize) -> Option<&T> {
+        if idx >= self.n {
+            return None;
+        }
+
+        unsafe {
+            let node = (&*self.find_pred(idx)).next[0];
+            Some(&(*node).value)
+        }
+    }
+
+    pub fn get_mut(&mut self, idx : usize) -> Option<&mut T> {
+        if idx >= self.n {
+            return None;
+        }
+
+        unsafe {
+            let node = (&*self.find_pred(idx)).next[0];
+            Some(&mut (*node).value)
+        }
+    }
+
+    pub fn set(&mut self, idx : usize, x : T) -> Option<T> {
+        self.get_mut(idx).map(|v| mem::replace(v, x))
+    }
+
+    pub fn add(&mut self, idx : usize, x : T) {
+        if idx > self.n {
+            return;
+        }
+
+        let k = self.pick_height();
+        self.grow(k);
+
+        let w = Box::into_raw(Box::new(Node::new(x, k)));
+
+        let mut u : *mut Node<T> = self.sentinel.as_mut();
+        let mut j = 0;
+
+        for r in (0..=self.h).rev() {
+            let mut node = unsafe { &mut *u };
+            while !node.next[r].is_null() && j + node.length[r] <= idx {
+                j += node.length[r];
+                u = node.next[r];
+                node = unsafe { &mut *u };
+            }
+
+            node.length[r] += 1;
+
+            if r <= k {
+                let new_node = unsafe { &mut *w };
+                new_node.next[r] = node.next[r];
+                node.next[r] = w;
+
+                new_node.length[r] = node.length[r] - (idx + 1 - j);
+                node.length[r] = idx + 1 - j;
+            }
+        }
+
+        self.n += 1;
+    }
+
+    pub fn push_back(&mut self, x : T) {
+        self.add(self.n, x)
+    }
+
+    pub fn remove(&mut self, idx : usize) -> Option<T> {
+        if idx >= self.n {
+            return None;
+        }
+
+        let sentinel : *mut Node<T> = self.sentinel.as_mut();
+        let mut u = sentinel;
+        let mut j = 0;
+        let mut del = ptr::null_mut();
+
+        for r in (0..=self.h).rev() {
+            let mut node = unsafe { &mut *u };
+            while !node.next[r].is_null() && j + node.length[r] <= idx {
+                j += node.length[r];
+                u = node.next[r];
+                node = unsafe { &mut *u };
+            }
+
+            node.length[r] -= 1;
+
+            if j + node.length[r] == idx && !node.next[r].is_null() {
+                del = node.next[r];
+                let del_node = unsafe { &*del };
+
+                node.length[r] += del_node.length[r];
+                node.next[r] = del_node.next[r];
+
+                if u == sentinel && node.next[r].is_null() && self.h > 0 {
+                    self.h -= 1;
+                }
+            }
+        }
+
+        self.n -= 1;
+
+        let del = unsafe { Box::from_raw(del) };
+        Some(del.value)
+    }
+
+    // returns the last node on each level together with its position (1-indexed, 0 for sentinel)
+    fn last_nodes(&mut self, top : usize) -> Vec<(*mut Node<T>, usize)> {
+        let mut lasts = vec![(ptr::null_mut(), 0); top + 1];
+        let mut u : *mut Node<T> = self.sentinel.as_mut();
+        let mut j = 0;
+
+        for r in (0..=top).rev() {
+            let mut node = unsafe { &*u };
+            while !node.next[r].is_null() {
+                j += node.length[r];
+                u = node.next[r];
+                node = unsafe { &*u };
+            }
+
+            lasts[r] = (u, j);
+        }
+
+        lasts
+    }
+
+    // appends all elements of other to self, leaving other empty
+    pub fn absorb(&mut self, other : &mut Self) {
+        if other.n == 0 {
+            return;
+        }
+
+        let top = std::cmp::max(self.h, other.h);
+        self.grow(top);
+        let lasts = self.last_nodes(top);
+
+        for (r, &(u, j)) in lasts.iter().enumerate() {
+            let node = unsafe { &mut *u };
+            let first = other.sentinel.next[r];
+            if !first.is_null() {
+                node.next[r] = first;
+                node.length[r] = self.n - j + other.sentinel.length[r];
+            }
+            else {
+                node.length[r] += other.n;
+            }
+
+            other.sentinel.next[r] = ptr::null_mut();
+            other.sentinel.length[r] = 0;
+        }
+
+        self.n += other.n;
+
+        other.h = 0;
+        other.n = 0;
+    }
+
+    // removes all elements at idx and after, and returns them as a new list
+    pub fn truncate(&mut self, idx : usize) -> Self {
+        let mut tail = Self::with_rng(StdRng::from_rng(&mut self.rng).unwrap());
+        if idx >= self.n {
+            return tail;
+        }
+
+        let mut u : *mut Node<T> = self.sentinel.as_mut();
+        let mut j = 0;
+
+        for r in (0..=self.h).rev() {
+            let mut node = unsafe { &mut *u };
+            while !node.next[r].is_null() && j + node.length[r] <= idx {
+                j += node.length[r];
+                u = node.next[r];
+                node = unsafe { &mut *u };
+            }
+
+            let first = node.next[r];
+            if !first.is_null() {
+                tail.sentinel.next[r] = first;
+                tail.sentinel.length[r] = j + node.length[r] - idx;
+                if tail.h == 0 {
+                    tail.h = r;
+                }
+            }
+            else {
+                tail.sentinel.length[r] = self.n - idx;
+            }
+
+            node.next[r] = ptr::null_mut();
+            node.length[r] = idx - j;
+        }
+
+        tail.n = self.n - idx;
+        self.n = idx;
+
+        while self.h > 0 && self.sentinel.next[self.h].is_null() {
+            self.h -= 1;
+        }
+
+        tail
+    }
+
+    pub fn iter(&self) -> SkiplistListIter<'_, T> {
+        SkiplistListIter { ptr : self.sentinel.next[0], rem : self.n, _marker : PhantomData }
+    }
+}
+
+impl<T : Default> Default for SkiplistList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T : Default> Drop for SkiplistList<T> {
+    fn drop(&mut self) {
+        let mut u = self.sentinel.next[0];
+
+        while !u.is_null() {
+            let node = unsafe { Box::from_raw(u) };
+            u = node.next[0];
+        }
+    }
+}
+
+pub struct SkiplistListIter<'a, T> {
+    ptr : *const Node<T>,
+    rem : usize,
+    _marker : PhantomData<&'a T>
+}
+
+impl<'a, T> Iterator for SkiplistListIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ptr.is_null() {
+            return None;
+        }
+
+        unsafe {
+            let node = &*self.ptr;
+            self.ptr = node.next[0];
+            self.rem -= 1;
+
+            Some(&node.value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rem, Some(self.rem))
+    }
+}
+
+impl<'a, T : Default> IntoIterator for &'a SkiplistList<T> {
+    type Item = &'a T;
+    type IntoIter = SkiplistListIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skiplist_list_works() {
+        let mut list = SkiplistList::<i32>::with_seed(42);
+
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        list.push_back(4);
+
+        assert_eq!(list.get(0), Some(&1));
+        assert_eq!(list.get(3), Some(&4));
+        assert_eq!(list.get(4), None);
+
+        list.add(0, -1);
+        list.add(2, 0);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![-1, 1, 0, 2, 3, 4]);
+
+        assert_eq!(list.set(1, 10), Some(1));
+        assert_eq!(list.remove(2), Some(0));
+        assert_eq!(list.remove(0), Some(-1));
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![10, 2, 3, 4]);
+        assert_eq!(list.remove(4), None);
+
+        let mut model = Vec::new();
+        list = SkiplistList::with_seed(7);
+        for i in 0i32..200 {
+            let idx = (i as usize * 7) % (model.len() + 1);
+            list.add(idx, i);
+            model.insert(idx, i);
+        }
+        for (i, x) in model.iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+        for i in 0..100 {
+            let idx = (i * 13) % model.len();
+            assert_eq!(list.remove(idx), Some(model.remove(idx)));
+        }
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), model);
+
+        let mut tail = list.truncate(40);
+        assert_eq!(list.size(), 40);
+        assert_eq!(tail.size(), 60);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), &model[..40]);
+        assert_eq!(tail.iter().cloned().collect::<Vec<_>>(), &model[40..]);
+
+        tail.add(0, -5);
+        model.insert(40, -5);
+        list.absorb(&mut tail);
+        assert_eq!(list.size(), 101);
+        assert_eq!(tail.size(), 0);
+        for (i, x) in model.iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+
+        list.push_back(1000);
+        tail.push_back(2000);
+        assert_eq!(list.get(101), Some(&1000));
+        assert_eq!(tail.get(0), Some(&2000));
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+use std::ops::Range;
+use crate::se_list::SEList;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Edit {
+    Insert(usize, String),
+    Delete(usize, String)
+}
+
+pub struct TextBuffer {
+    text : SEList<char>,
+    line_starts : Vec<usize>, // line_starts[k] = position of the first char of k-th line
+    undo_log : Vec<Edit>,
+    redo_log : Vec<Edit>
+}
+
+impl TextBuffer {
+    pub fn new() -> Self {
+        Self { text : SEList::new(), line_starts : vec![0], undo_log : Vec::new(), redo_log : Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    pub fn line_start(&self, k : usize) -> Option<usize> {
+        self.line_starts.get(k).cloned()
+    }
+
+    // pos must satisfy pos <= len
+    pub fn line_of(&self, pos : usize) -> usize {
+        match self.line_starts.binary_search(&pos) {
+            Ok(k) => k,
+            Err(k) => k - 1
+        }
+    }
+
+    pub fn slice(&self, range : Range<usize>) -> String {
+        let end = std::cmp::min(range.end, self.len());
+        if range.start >= end {
+            return String::new();
+        }
+
+        self.text.iter_from(range.start).take(end - range.start).collect()
+    }
+
+    fn apply_insert(&mut self, pos : usize, s : &str) {
+        let k = self.line_of(pos);
+        let mut m = 0;
+        let mut new_starts = Vec::new();
+
+        for c in s.chars() {
+            self.text.add(pos + m, c);
+            m += 1;
+
+            if c == '\n' {
+                new_starts.push(pos + m);
+            }
+        }
+
+        for start in self.line_starts[(k + 1)..].iter_mut() {
+            *start += m;
+        }
+
+        self.line_starts.splice((k + 1)..(k + 1), new_starts);
+    }
+
+    fn apply_delete(&mut self, range : Range<usize>) -> String {
+        let m = range.end - range.start;
+        let mut removed = String::with_capacity(m);
+
+        for _ in 0..m {
+            removed.push(self.text.remove(range.start).unwrap());
+        }
+
+        // the lines starting just after a removed newline are merged into the previous ones
+        self.line_starts.retain(|&s| s <= range.start || s > range.end);
+        for start in self.line_starts.iter_mut() {
+            if *start > range.end {
+                *start -= m;
+            }
+        }
+
+        removed
+    }
+
+    pub fn insert_str(&mut self, pos : usize, s : &str) {
+        if pos > self.len() || s.is_empty() {
+            return;
+        }
+
+        self.apply_insert(pos, s);
+
+        self.undo_log.push(Edit::Insert(pos, s.to_string()));
+        self.redo_log.clear();
+    }
+
+    pub fn delete(&mut self, range : Range<usize>) -> Option<String> {
+        if range.start > range.end || range.end > self.len() {
+            return None;
+        }
+        if range.start == range.end {
+            return Some(String::new());
+        }
+
+        let pos = range.start;
+        let removed = self.apply_delete(range);
+
+        self.undo_log.push(Edit::Delete(pos, removed.clone()));
+        self.redo_log.clear();
+
+        Some(removed)
+    }
+
+    fn revert(&mut self, edit : &Edit) {
+        match edit {
+            Edit::Insert(pos, s) => { self.apply_delete(*pos..(*pos + s.chars().count())); },
+            Edit::Delete(pos, s) => self.apply_insert(*pos, s)
+        }
+    }
+
+    fn replay(&mut self, edit : &Edit) {
+        match edit {
+            Edit::Insert(pos, s) => self.apply_insert(*pos, s),
+            Edit::Delete(pos, s) => { self.apply_delete(*pos..(*pos + s.chars().count())); }
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        if let Some(edit) = self.undo_log.pop() {
+            self.revert(&edit);
+            self.redo_log.push(edit);
+
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        if let Some(edit) = self.redo_log.pop() {
+            self.replay(&edit);
+            self.undo_log.push(edit);
+
+            true
+        }
+        else {
+            false
+        }
+    }
+}
+
+impl Default for TextBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&str> for TextBuffer {
+    fn from(s : &str) -> Self {
+        let mut buf = Self::new();
+        buf.apply_insert(0, s);
+
+        buf
+    }
+}
+
+impl fmt::Display for TextBuffer {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.text.iter() {
+            write!(f, "{}", c)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_buffer_works() {
+        let mut buf = TextBuffer::from("hello\nworld");
+
+        assert_eq!(buf.len(), 11);
+        assert_eq!(buf.line_count(), 2);
+        assert_eq!(buf.line_start(1), Some(6));
+        assert_eq!(buf.line_of(5), 0);
+        assert_eq!(buf.line_of(6), 1);
+
+        buf.insert_str(5, ",\nbrave new");
+        assert_eq!(buf.to_string(), "hello,\nbrave new\nworld");
+        assert_eq!(buf.line_count(), 3);
+        assert_eq!(buf.line_start(1), Some(7));
+        assert_eq!(buf.line_start(2), Some(17));
+        assert_eq!(buf.slice(7..12), "brave");
+
+        assert_eq!(buf.delete(5..13), Some(",\nbrave ".to_string()));
+        assert_eq!(buf.to_string(), "hellonew\nworld");
+        assert_eq!(buf.line_count(), 2);
+        assert_eq!(buf.line_start(1), Some(9));
+        assert_eq!(buf.delete(10..20), None);
+
+        assert!(buf.undo());
+        assert_eq!(buf.to_string(), "hello,\nbrave new\nworld");
+        assert_eq!(buf.line_start(2), Some(17));
+        assert!(buf.undo());
+        assert_eq!(buf.to_string(), "hello\nworld");
+        assert!(!buf.undo());
+
+        assert!(buf.redo());
+        assert!(buf.redo());
+        assert_eq!(buf.to_string(), "hellonew\nworld");
+        assert!(!buf.redo());
+
+        buf.insert_str(0, "\u{3042}\n");
+        assert_eq!(buf.line_of(1), 0);
+        assert_eq!(buf.line_of(2), 1);
+        assert_eq!(buf.slice(0..3), "\u{3042}\nh");
+        assert!(!buf.redo());
+    }
+}
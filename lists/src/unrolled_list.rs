@@ -0,0 +1,303 @@
+use std::ptr;
+use std::mem::MaybeUninit;
+use std::slice;
+
+// block of at most B elements stored inline, where the first len slots are initialized
+struct Block<T, const B : usize> {
+    len : usize,
+    items : [MaybeUninit<T>; B]
+}
+
+impl<T, const B : usize> Block<T, B> {
+    fn new() -> Box<Self> {
+        Box::new(Self { len : 0, items : unsafe { MaybeUninit::uninit().assume_init() } })
+    }
+
+    fn as_ptr(&self) -> *const T {
+        self.items.as_ptr() as *const T
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.items.as_mut_ptr() as *mut T
+    }
+
+    fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+
+    // idx must satisfy idx <= len < B
+    fn insert(&mut self, idx : usize, x : T) {
+        unsafe {
+            let p = self.as_mut_ptr().add(idx);
+            ptr::copy(p, p.add(1), self.len - idx);
+            ptr::write(p, x);
+        }
+
+        self.len += 1;
+    }
+
+    // idx must satisfy idx < len
+    fn remove(&mut self, idx : usize) -> T {
+        self.len -= 1;
+
+        unsafe {
+            let p = self.as_mut_ptr().add(idx);
+            let x = ptr::read(p);
+            ptr::copy(p.add(1), p, self.len - idx);
+
+            x
+        }
+    }
+
+    // moves the elements at idx and after into a new block
+    fn split_off(&mut self, idx : usize) -> Box<Self> {
+        let mut other = Self::new();
+        other.len = self.len - idx;
+
+        unsafe {
+            ptr::copy_nonoverlapping(self.as_ptr().add(idx), other.as_mut_ptr(), other.len);
+        }
+
+        self.len = idx;
+        other
+    }
+
+    // len + other.len must be at most B
+    fn append(&mut self, other : &mut Self) {
+        unsafe {
+            ptr::copy_nonoverlapping(other.as_ptr(), self.as_mut_ptr().add(self.len), other.len);
+        }
+
+        self.len += other.len;
+        other.len = 0;
+    }
+}
+
+impl<T, const B : usize> Drop for Block<T, B> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.as_mut_slice());
+        }
+    }
+}
+
+// the default B = 16 cannot depend on T, it spans one or two cache lines for 4 to 8 byte elements, and
+// B = 64 / size_of::<T>() makes a block fit in exactly one
+pub struct UnrolledList<T, const B : usize = 16> {
+    blocks : Vec<Box<Block<T, B>>>,
+    n : usize
+}
+
+impl<T, const B : usize> UnrolledList<T, B> {
+    pub fn new() -> Self {
+        assert!(B >= 2, "UnrolledList needs at least 2 elements per block.");
+
+        Self { blocks : Vec::new(), n : 0 }
+    }
+
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    // idx must satisfy idx < n, or idx == n to locate the end
+    fn locate(&self, idx : usize) -> (usize, usize) {
+        let mut rem = idx;
+        for (i, block) in self.blocks.iter().enumerate() {
+            if rem < block.len {
+                return (i, rem);
+            }
+            rem -= block.len;
+        }
+
+        let last = self.blocks.len() - 1;
+        (last, self.blocks[last].len)
+    }
+
+    pub fn get(&self, idx : usize) -> Option<&T> {
+        if idx >= self.n {
+            return None;
+        }
+
+        let (b, i) = self.locate(idx);
+        self.blocks[b].as_slice().get(i)
+    }
+
+    pub fn get_mut(&mut self, idx : usize) -> Option<&mut T> {
+        if idx >= self.n {
+            return None;
+        }
+
+        let (b, i) = self.locate(idx);
+        self.blocks[b].as_mut_slice().get_mut(i)
+    }
+
+    pub fn set(&mut self, idx : usize, x : T) -> Option<T> {
+        self.get_mut(idx).map(|v| std::mem::replace(v, x))
+    }
+
+    pub fn add(&mut self, idx : usize, x : T) {
+        if idx > self.n {
+            return;
+        }
+
+        if self.blocks.is_empty() {
+            self.blocks.push(Block::new());
+        }
+
+        let (mut b, mut i) = self.locate(idx);
+
+        if self.blocks[b].len == B {
+            let half = self.blocks[b].split_off(B / 2);
+            self.blocks.insert(b + 1, half);
+
+            if i > B / 2 {
+                b += 1;
+                i -= B / 2;
+            }
+        }
+
+        self.blocks[b].insert(i, x);
+        self.n += 1;
+    }
+
+    pub fn push_back(&mut self, x : T) {
+        self.add(self.n, x)
+    }
+
+    pub fn remove(&mut self, idx : usize) -> Option<T> {
+        if idx >= self.n {
+            return None;
+        }
+
+        let (b, i) = self.locate(idx);
+        let x = self.blocks[b].remove(i);
+        self.n -= 1;
+
+        // every block but a sole one keeps at least B/2 elements
+        if self.blocks[b].len < B / 2 && self.blocks.len() > 1 {
+            let (l, r) = if b + 1 < self.blocks.len() { (b, b + 1) } else { (b - 1, b) };
+
+            if self.blocks[l].len + self.blocks[r].len <= B {
+                let mut right = self.blocks.remove(r);
+                self.blocks[l].append(&mut right);
+            }
+            else if l == b {
+                let y = self.blocks[r].remove(0);
+                let len = self.blocks[l].len;
+                self.blocks[l].insert(len, y);
+            }
+            else {
+                let len = self.blocks[l].len;
+                let y = self.blocks[l].remove(len - 1);
+                self.blocks[r].insert(0, y);
+            }
+        }
+        else if self.blocks[b].len == 0 {
+            self.blocks.remove(b);
+        }
+
+        Some(x)
+    }
+
+    pub fn iter(&self) -> UnrolledListIter<'_, T, B> {
+        UnrolledListIter { blocks : self.blocks.iter(), cur : [].iter(), rem : self.n }
+    }
+}
+
+impl<T, const B : usize> Default for UnrolledList<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct UnrolledListIter<'a, T, const B : usize> {
+    blocks : slice::Iter<'a, Box<Block<T, B>>>,
+    cur : slice::Iter<'a, T>,
+    rem : usize
+}
+
+impl<'a, T, const B : usize> Iterator for UnrolledListIter<'a, T, B> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(x) = self.cur.next() {
+                self.rem -= 1;
+                return Some(x);
+            }
+
+            self.cur = self.blocks.next()?.as_slice().iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rem, Some(self.rem))
+    }
+}
+
+impl<'a, T, const B : usize> IntoIterator for &'a UnrolledList<T, B> {
+    type Item = &'a T;
+    type IntoIter = UnrolledListIter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unrolled_list_works() {
+        let mut list = UnrolledList::<i32, 4>::new();
+
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        list.push_back(4);
+
+        assert_eq!(list.get(0), Some(&1));
+        assert_eq!(list.get(3), Some(&4));
+        assert_eq!(list.get(4), None);
+
+        list.add(0, -1);
+        list.add(2, 0);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![-1, 1, 0, 2, 3, 4]);
+
+        assert_eq!(list.set(1, 10), Some(1));
+        assert_eq!(list.remove(2), Some(0));
+        assert_eq!(list.remove(0), Some(-1));
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![10, 2, 3, 4]);
+
+        let mut model = vec![10, 2, 3, 4];
+        for i in 0i32..100 {
+            list.add(model.len() / 3, i);
+            model.insert(model.len() / 3, i);
+        }
+        list.add(0, -5);
+        model.insert(0, -5);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), model);
+
+        while !model.is_empty() {
+            let idx = (model.len() * 2) / 3;
+            assert_eq!(list.remove(idx), Some(model.remove(idx)));
+        }
+        assert!(list.is_empty());
+
+        let mut strings = UnrolledList::<String>::new();
+        for i in 0..40 {
+            strings.add(i / 2, i.to_string());
+        }
+        assert_eq!(strings.remove(0), Some("1".to_string()));
+        assert_eq!(strings.size(), 39);
+    }
+}
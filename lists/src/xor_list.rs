@@ -0,0 +1,238 @@
+use std::mem;
+
+// index of the nodes is used as the address, and 0 stands for null
+const NIL : usize = 0;
+
+#[derive(Debug)]
+struct Node<T : Default> {
+    value : T,
+    link : usize // prev ^ next, or the next free node if unused
+}
+
+#[derive(Debug)]
+pub struct XorList<T : Default> {
+    nodes : Vec<Node<T>>,
+    head : usize,
+    tail : usize,
+    free : usize,
+    n : usize
+}
+
+impl<T : Default> XorList<T> {
+    pub fn new() -> Self {
+        Self { nodes : vec![Node { value : T::default(), link : NIL }], head : NIL, tail : NIL, free : NIL, n : 0 }
+    }
+
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    fn alloc(&mut self, value : T, link : usize) -> usize {
+        if self.free == NIL {
+            self.nodes.push(Node { value, link });
+            return self.nodes.len() - 1;
+        }
+
+        let idx = self.free;
+        self.free = self.nodes[idx].link;
+        self.nodes[idx] = Node { value, link };
+
+        idx
+    }
+
+    fn release(&mut self, idx : usize) -> T {
+        let node = &mut self.nodes[idx];
+        node.link = self.free;
+        self.free = idx;
+
+        mem::take(&mut node.value)
+    }
+
+    // head and tail are symmetric, so both ends share the same code
+    fn push_end(&mut self, x : T, front : bool) {
+        let end = if front { self.head } else { self.tail };
+        let idx = self.alloc(x, end);
+
+        if end == NIL {
+            self.head = idx;
+            self.tail = idx;
+        }
+        else {
+            self.nodes[end].link ^= idx;
+
+            if front { self.head = idx; } else { self.tail = idx; }
+        }
+
+        self.n += 1;
+    }
+
+    fn pop_end(&mut self, front : bool) -> Option<T> {
+        let end = if front { self.head } else { self.tail };
+        if end == NIL {
+            return None;
+        }
+
+        let next = self.nodes[end].link;
+        if next == NIL {
+            self.head = NIL;
+            self.tail = NIL;
+        }
+        else {
+            self.nodes[next].link ^= end;
+
+            if front { self.head = next; } else { self.tail = next; }
+        }
+
+        self.n -= 1;
+
+        Some(self.release(end))
+    }
+
+    pub fn push_front(&mut self, x : T) {
+        self.push_end(x, true)
+    }
+
+    pub fn push_back(&mut self, x : T) {
+        self.push_end(x, false)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.pop_end(true)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.pop_end(false)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        if self.head == NIL { None } else { Some(&self.nodes[self.head].value) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        if self.tail == NIL { None } else { Some(&self.nodes[self.tail].value) }
+    }
+
+    pub fn reverse(&mut self) {
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    pub fn iter(&self) -> XorListIter<'_, T> {
+        XorListIter {
+            nodes : &self.nodes,
+            front : (NIL, self.head),
+            back : (NIL, self.tail),
+            rem : self.n
+        }
+    }
+}
+
+impl<T : Default> Default for XorList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct XorListIter<'a, T : Default> {
+    nodes : &'a [Node<T>],
+    front : (usize, usize), // (prev, cur)
+    back : (usize, usize), // (next, cur)
+    rem : usize
+}
+
+impl<'a, T : Default> XorListIter<'a, T> {
+    fn step(&mut self, front : bool) -> Option<&'a T> {
+        if self.rem == 0 {
+            return None;
+        }
+
+        let (prev, cur) = if front { self.front } else { self.back };
+        let next = self.nodes[cur].link ^ prev;
+
+        if front { self.front = (cur, next); } else { self.back = (cur, next); }
+        self.rem -= 1;
+
+        Some(&self.nodes[cur].value)
+    }
+}
+
+impl<'a, T : Default> Iterator for XorListIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step(true)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rem, Some(self.rem))
+    }
+}
+
+impl<'a, T : Default> DoubleEndedIterator for XorListIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.step(false)
+    }
+}
+
+impl<'a, T : Default> IntoIterator for &'a XorList<T> {
+    type Item = &'a T;
+    type IntoIter = XorListIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xor_list_works() {
+        let mut list = XorList::<i32>::new();
+
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_back(1);
+        list.push_back(2);
+        list.push_front(0);
+        list.push_back(3);
+
+        assert_eq!(list.size(), 4);
+        assert_eq!(list.front(), Some(&0));
+        assert_eq!(list.back(), Some(&3));
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(list.iter().rev().cloned().collect::<Vec<_>>(), vec![3, 2, 1, 0]);
+
+        let mut it = list.iter();
+        assert_eq!(it.next(), Some(&0));
+        assert_eq!(it.next_back(), Some(&3));
+        assert_eq!(it.next(), Some(&1));
+        assert_eq!(it.next_back(), Some(&2));
+        assert_eq!(it.next(), None);
+
+        list.reverse();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![3, 2, 1, 0]);
+        assert_eq!(list.pop_front(), Some(3));
+        assert_eq!(list.pop_back(), Some(0));
+
+        list.push_front(4);
+        list.push_back(5);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![4, 2, 1, 5]);
+
+        list.reverse();
+        assert_eq!(list.pop_front(), Some(5));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_front(), Some(4));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+
+        list.push_back(6);
+        assert_eq!(list.front(), list.back());
+    }
+}
@@ -0,0 +1,40 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use sorts::*;
+
+const SIZES : [usize; 2] = [10_000, 100_000];
+
+type Sort = fn(&mut [u64]);
+
+fn bench_shape(c : &mut Criterion, shape : Shape) {
+    let mut group = c.benchmark_group(format!("{:?}", shape));
+
+    for &n in SIZES.iter() {
+        let input = shape.generate(n, 0);
+        let sorts : [(&str, Sort); 6] = [
+            ("tim_sort", tim_sort),
+            ("intro_sort", intro_sort),
+            ("heap_sort", heap_sort),
+            ("merge_sort", merge_sort),
+            ("slice::sort", <[u64]>::sort),
+            ("slice::sort_unstable", <[u64]>::sort_unstable)
+        ];
+
+        for (name, sort) in sorts.iter() {
+            group.bench_with_input(BenchmarkId::new(*name, n), &input, |b, input| {
+                b.iter_batched_ref(|| input.clone(), |v| sort(v), BatchSize::LargeInput)
+            });
+        }
+    }
+
+    group.finish();
+}
+
+// nearly sorted inputs are what the adaptive sorts are for, and the others show their overhead
+fn adaptive(c : &mut Criterion) {
+    for &shape in [Shape::NearlySorted, Shape::Sorted, Shape::Reversed, Shape::Random].iter() {
+        bench_shape(c, shape);
+    }
+}
+
+criterion_group!(benches, adaptive);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+// prints the work done by each sort across input shapes and sizes:
+//     cargo run --release -p sorts --features instrument --example sort_stats
+use sorts::*;
+
+const SIZES : [usize; 3] = [1_000, 10_000, 100_000];
+
+fn run(name : &str, shape : Shape, n : usize, sort : fn(&mut [Counted<u64>])) {
+    let mut v : Vec<_> = shape.generate(n, 0).into_iter().map(Counted).collect();
+    let ((), stats) = measure(|| sort(&mut v));
+    assert!(v.windows(2).all(|w| w[0].0 <= w[1].0));
+
+    println!("{:<10} {:>8} {:<12} {:>12} {:>12} {:>12} {:>6} {:>12}",
+        format!("{:?}", shape), n, name, stats.comparisons, stats.swaps, stats.moves, stats.allocations, stats.allocated_bytes);
+}
+
+fn main() {
+    println!("{:<10} {:>8} {:<12} {:>12} {:>12} {:>12} {:>6} {:>12}", "shape", "n", "sort", "comparisons", "swaps", "moves", "allocs", "bytes");
+
+    for &shape in Shape::ALL.iter() {
+        for &n in SIZES.iter() {
+            run("merge_sort", shape, n, merge_sort);
+            run("quick_sort", shape, n, quick_sort);
+            run("heap_sort", shape, n, heap_sort);
+            run("tim_sort", shape, n, tim_sort);
+            run("intro_sort", shape, n, intro_sort);
+            run("radix_sort", shape, n, |v| radix_sort_by_key(v, |x| x.0));
+        }
+    }
+}
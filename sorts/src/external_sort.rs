@@ -0,0 +1,344 @@
+use std::cmp::Ordering;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use heaps::{BinaryHeap, PeekMut};
+use crate::merge_sort::merge_sort_by;
+
+const DEFAULT_MEMORY_BUDGET : usize = 256 << 20;
+const DEFAULT_FAN_IN : usize = 64;
+
+// encodes and decodes the records of the input, the runs and the output
+pub trait Codec {
+    type Record;
+
+    // None at the end of the input
+    fn read<R : BufRead>(&self, r : &mut R) -> io::Result<Option<Self::Record>>;
+
+    fn write<W : Write>(&self, w : &mut W, record : &Self::Record) -> io::Result<()>;
+
+    // estimated bytes the record occupies in memory
+    fn size(&self, record : &Self::Record) -> usize;
+}
+
+// binary records of exactly N bytes, compared bytewise
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FixedWidth<const N : usize>;
+
+impl<const N : usize> Codec for FixedWidth<N> {
+    type Record = [u8; N];
+
+    fn read<R : BufRead>(&self, r : &mut R) -> io::Result<Option<[u8; N]>> {
+        let mut buf = [0; N];
+        let mut filled = 0;
+        while filled < N {
+            match r.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(k) => filled += k,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e)
+            }
+        }
+
+        match filled {
+            0 => Ok(None),
+            _ if filled < N => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated fixed-width record")),
+            _ => Ok(Some(buf))
+        }
+    }
+
+    fn write<W : Write>(&self, w : &mut W, record : &[u8; N]) -> io::Result<()> {
+        w.write_all(record)
+    }
+
+    fn size(&self, _record : &[u8; N]) -> usize {
+        N
+    }
+}
+
+// newline-delimited text, where every output line ends with '\n' even if the last input line does not
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lines;
+
+impl Codec for Lines {
+    type Record = String;
+
+    fn read<R : BufRead>(&self, r : &mut R) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if r.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        if line.ends_with('\n') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+
+    fn write<W : Write>(&self, w : &mut W, record : &String) -> io::Result<()> {
+        w.write_all(record.as_bytes())?;
+        w.write_all(b"\n")
+    }
+
+    fn size(&self, record : &String) -> usize {
+        record.capacity()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExternalSortStats {
+    pub records : u64,
+    pub runs : usize, // sorted chunks, which are spilled unless the whole input fits in the budget
+    pub merge_passes : usize
+}
+
+// a sorted run spilled to a temporary file, which is deleted on drop
+struct Run(PathBuf);
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+static RUN_COUNTER : AtomicUsize = AtomicUsize::new(0);
+
+fn run_file_name(id : usize) -> String {
+    format!("ods-external-sort-{}-{}.run", std::process::id(), id)
+}
+
+#[derive(Debug, Clone)]
+pub struct ExternalSort {
+    memory_budget : usize,
+    fan_in : usize,
+    temp_dir : PathBuf
+}
+
+impl ExternalSort {
+    pub fn new() -> Self {
+        Self { memory_budget : DEFAULT_MEMORY_BUDGET, fan_in : DEFAULT_FAN_IN, temp_dir : std::env::temp_dir() }
+    }
+
+    // bytes of records held in memory at once, estimated by Codec::size
+    pub fn memory_budget(mut self, bytes : usize) -> Self {
+        self.memory_budget = bytes;
+        self
+    }
+
+    // number of runs merged at once, which bounds the open files
+    pub fn fan_in(mut self, fan_in : usize) -> Self {
+        self.fan_in = fan_in.max(2);
+        self
+    }
+
+    pub fn temp_dir<P : AsRef<Path>>(mut self, dir : P) -> Self {
+        self.temp_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn sort<C, R, W>(&self, codec : &C, input : R, output : W) -> io::Result<ExternalSortStats>
+        where C : Codec, C::Record : Ord, R : Read, W : Write
+    {
+        self.sort_by(codec, input, output, |a, b| a.cmp(b))
+    }
+
+    // stable, reading the input once and writing every record 1 + merge_passes times besides the output
+    pub fn sort_by<C, R, W, F>(&self, codec : &C, input : R, output : W, compare : F) -> io::Result<ExternalSortStats>
+        where C : Codec, R : Read, W : Write, F : Fn(&C::Record, &C::Record) -> Ordering
+    {
+        let mut input = BufReader::new(input);
+        let mut output = BufWriter::new(output);
+        let mut stats = ExternalSortStats::default();
+
+        let mut runs = Vec::new();
+        let mut chunk = Vec::new();
+        let mut used = 0;
+        loop {
+            let record = codec.read(&mut input)?;
+            let done = record.is_none();
+
+            if let Some(record) = record {
+                used += codec.size(&record) + std::mem::size_of::<C::Record>();
+                chunk.push(record);
+                stats.records += 1;
+            }
+
+            if !chunk.is_empty() && (done || used >= self.memory_budget) {
+                merge_sort_by(&mut chunk, &compare);
+                stats.runs += 1;
+
+                if done && runs.is_empty() {
+                    for record in chunk.iter() {
+                        codec.write(&mut output, record)?;
+                    }
+                    break;
+                }
+
+                runs.push(self.spill(codec, &chunk)?);
+                chunk.clear();
+                used = 0;
+            }
+
+            if done {
+                break;
+            }
+        }
+
+        // merges consecutive groups of runs, so that ties still come in the input order
+        while runs.len() > self.fan_in {
+            let mut merged = Vec::with_capacity(runs.len().div_ceil(self.fan_in));
+            let mut rest = runs.into_iter();
+            loop {
+                let group : Vec<Run> = rest.by_ref().take(self.fan_in).collect();
+                if group.len() <= 1 {
+                    merged.extend(group);
+                    break;
+                }
+
+                let (run, file) = self.create_run()?;
+                let mut w = BufWriter::new(file);
+                merge_runs(codec, &group, &mut w, &compare)?;
+                w.flush()?;
+                merged.push(run);
+            }
+
+            runs = merged;
+            stats.merge_passes += 1;
+        }
+
+        if !runs.is_empty() {
+            merge_runs(codec, &runs, &mut output, &compare)?;
+            stats.merge_passes += 1;
+        }
+        output.flush()?;
+
+        Ok(stats)
+    }
+
+    // creates a new file for a run, never opening one that already exists, e.g. a link planted in a shared directory
+    fn create_run(&self) -> io::Result<(Run, File)> {
+        loop {
+            let path = self.temp_dir.join(run_file_name(RUN_COUNTER.fetch_add(1, AtomicOrdering::Relaxed)));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok((Run(path), file)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e)
+            }
+        }
+    }
+
+    fn spill<C : Codec>(&self, codec : &C, records : &[C::Record]) -> io::Result<Run> {
+        let (run, file) = self.create_run()?;
+        let mut w = BufWriter::new(file);
+        for record in records.iter() {
+            codec.write(&mut w, record)?;
+        }
+        w.flush()?;
+
+        Ok(run)
+    }
+}
+
+impl Default for ExternalSort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// k-way merges the runs into w, keeping one record of each run in the heap
+fn merge_runs<C, W, F>(codec : &C, runs : &[Run], w : &mut W, compare : &F) -> io::Result<()>
+    where C : Codec, W : Write, F : Fn(&C::Record, &C::Record) -> Ordering
+{
+    let mut readers = runs.iter().map(|run| File::open(&run.0).map(BufReader::new)).collect::<io::Result<Vec<_>>>()?;
+
+    let mut heap = BinaryHeap::by(|a : &(C::Record, usize), b : &(C::Record, usize)| compare(&a.0, &b.0).then(a.1.cmp(&b.1)));
+    for (i, r) in readers.iter_mut().enumerate() {
+        if let Some(record) = codec.read(r)? {
+            heap.insert((record, i));
+        }
+    }
+
+    while let Some(mut top) = heap.peek_mut() {
+        codec.write(w, &top.0)?;
+
+        match codec.read(&mut readers[top.1])? {
+            Some(record) => top.0 = record,
+            None => { PeekMut::pop(top); }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn external_sort_works() {
+        let dir = std::env::temp_dir().join(format!("ods-external-sort-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let sorter = ExternalSort::new().memory_budget(1 << 10).fan_in(3).temp_dir(&dir);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut lines : Vec<String> = (0..2000).map(|_| format!("{}", rng.gen_range(0, 100000))).collect();
+        let input = lines.join("\n");
+
+        let mut output = Vec::new();
+        let stats = sorter.sort(&Lines, input.as_bytes(), &mut output).unwrap();
+        lines.sort();
+        assert_eq!(String::from_utf8(output).unwrap(), lines.join("\n") + "\n");
+        assert_eq!(stats.records, 2000);
+        assert!(stats.runs > 3);
+        assert!(stats.merge_passes > 1);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        // records of a 4-byte key and a 4-byte sequence number, sorted by the key only
+        let records : Vec<[u8; 8]> = (0..1000u32).map(|i| {
+            let mut r = [0; 8];
+            r[..4].copy_from_slice(&rng.gen_range(0u32, 50).to_be_bytes());
+            r[4..].copy_from_slice(&i.to_be_bytes());
+            r
+        }).collect();
+        let input : Vec<u8> = records.iter().flat_map(|r| r.iter().copied()).collect();
+
+        let mut output = Vec::new();
+        sorter.sort_by(&FixedWidth::<8>, Cursor::new(input), &mut output, |a, b| a[..4].cmp(&b[..4])).unwrap();
+        let mut expected = records;
+        expected.sort_by(|a, b| a[..4].cmp(&b[..4]));
+        assert_eq!(output, expected.iter().flat_map(|r| r.iter().copied()).collect::<Vec<_>>());
+
+        let mut output = Vec::new();
+        let stats = ExternalSort::new().sort(&Lines, "b\na\r\nc".as_bytes(), &mut output).unwrap();
+        assert_eq!(output, b"a\r\nb\nc\n");
+        assert_eq!(stats, ExternalSortStats { records : 3, runs : 1, merge_passes : 0 });
+
+        let mut output = Vec::new();
+        assert_eq!(sorter.sort(&Lines, io::empty(), &mut output).unwrap().records, 0);
+        assert!(output.is_empty());
+
+        let err = sorter.sort(&FixedWidth::<4>, &[1u8, 2, 3, 4, 5][..], &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        // files already taking the names of the next runs are skipped, not truncated
+        let next = RUN_COUNTER.load(AtomicOrdering::Relaxed);
+        let taken : Vec<PathBuf> = (next..next + 4).map(|id| dir.join(run_file_name(id))).collect();
+        for path in taken.iter() {
+            fs::write(path, "taken").unwrap();
+        }
+        let mut output = Vec::new();
+        sorter.sort(&Lines, lines.join("\n").as_bytes(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), lines.join("\n") + "\n");
+        for path in taken.iter() {
+            assert_eq!(fs::read_to_string(path).unwrap(), "taken");
+            fs::remove_file(path).unwrap();
+        }
+
+        fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+use std::cmp::Ordering;
+use heaps::{heapify_with, sort_heap_with, trickle_down_top_with};
+use crate::instrument::swap;
+
+// restores the max-heap after v[0] is replaced
+pub(crate) fn sift_down_top<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], is_less : &mut F) {
+    trickle_down_top_with::<2, _, _, _>(v, |a : &T, b : &T| is_less(b, a), swap);
+}
+
+// a max-heap w.r.t. is_less is a heap of the heaps crate w.r.t. the reversed order
+pub(crate) fn make_max_heap<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], is_less : &mut F) {
+    heapify_with::<2, _, _, _>(v, |a : &T, b : &T| is_less(b, a), swap);
+}
+
+// turns the max-heap into the ascending order by moving the greatest element to the back repeatedly
+pub(crate) fn sort_max_heap<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], is_less : &mut F) {
+    sort_heap_with::<2, _, _, _>(v, |a : &T, b : &T| is_less(b, a), swap);
+}
+
+pub(crate) fn heap_sort_with<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], mut is_less : F) {
+    make_max_heap(v, &mut is_less);
+    sort_max_heap(v, &mut is_less);
+}
+
+// unstable, O(n log n) time and O(1) extra space
+pub fn heap_sort<T : Ord>(v : &mut [T]) {
+    heap_sort_with(v, |a, b| a < b);
+}
+
+pub fn heap_sort_by<T, F : FnMut(&T, &T) -> Ordering>(v : &mut [T], mut compare : F) {
+    heap_sort_with(v, |a, b| compare(a, b) == Ordering::Less);
+}
+
+pub fn heap_sort_by_key<T, K : Ord, F : FnMut(&T) -> K>(v : &mut [T], mut f : F) {
+    heap_sort_with(v, |a, b| f(a) < f(b));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn heap_sort_works() {
+        let mut v = vec![5, 3, 4, 9, -1, 0];
+        heap_sort(&mut v);
+        assert_eq!(v, vec![-1, 0, 3, 4, 5, 9]);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for &n in [0, 1, 2, 3, 100, 1000].iter() {
+            let mut v : Vec<i32> = (0..n).map(|_| rng.gen_range(-50, 50)).collect();
+            let mut expected = v.clone();
+            expected.sort();
+
+            heap_sort(&mut v);
+            assert_eq!(v, expected);
+        }
+
+        let mut v : Vec<i32> = (0..1000).collect();
+        heap_sort_by(&mut v, |a, b| b.cmp(a));
+        assert_eq!(v, (0..1000).rev().collect::<Vec<_>>());
+
+        let mut words = vec!["pear", "fig", "banana", "kiwi"];
+        heap_sort_by_key(&mut words, |w| w.len());
+        assert_eq!(words[0], "fig");
+        assert_eq!(words[3], "banana");
+    }
+}
@@ -0,0 +1,194 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+// comparisons are counted whenever Counted or counting is used, while the other counters need the "instrument" feature
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortStats {
+    pub comparisons : u64,
+    pub swaps : u64,
+    pub moves : u64, // elements copied one way, e.g. into the buffer of merge sort
+    pub allocations : u64,
+    pub allocated_bytes : u64
+}
+
+impl SortStats {
+    fn add(&self, other : &Self) -> Self {
+        Self {
+            comparisons : self.comparisons + other.comparisons,
+            swaps : self.swaps + other.swaps,
+            moves : self.moves + other.moves,
+            allocations : self.allocations + other.allocations,
+            allocated_bytes : self.allocated_bytes + other.allocated_bytes
+        }
+    }
+}
+
+thread_local! {
+    static STATS : Cell<SortStats> = Cell::new(SortStats::default());
+}
+
+fn update<F : FnOnce(&mut SortStats)>(f : F) {
+    STATS.with(|stats| {
+        let mut s = stats.get();
+        f(&mut s);
+        stats.set(s);
+    });
+}
+
+#[inline(always)]
+fn record<F : FnOnce(&mut SortStats)>(f : F) {
+    #[cfg(feature = "instrument")]
+    update(f);
+    #[cfg(not(feature = "instrument"))]
+    let _ = f;
+}
+
+#[inline(always)]
+pub(crate) fn swap<T>(v : &mut [T], i : usize, j : usize) {
+    record(|s| s.swaps += 1);
+    v.swap(i, j);
+}
+
+#[inline(always)]
+pub(crate) fn record_moves(n : usize) {
+    record(|s| s.moves += n as u64);
+}
+
+#[inline(always)]
+pub(crate) fn record_alloc<T>(len : usize) {
+    record(|s| {
+        s.allocations += 1;
+        s.allocated_bytes += (len * std::mem::size_of::<T>()) as u64;
+    });
+}
+
+// runs f and returns what it did on the current thread
+pub fn measure<R, F : FnOnce() -> R>(f : F) -> (R, SortStats) {
+    let outer = STATS.with(|stats| stats.replace(SortStats::default()));
+    let r = f();
+    let inner = STATS.with(|stats| stats.replace(SortStats::default()));
+    STATS.with(|stats| stats.set(outer.add(&inner)));
+
+    (r, inner)
+}
+
+// wraps a comparator of the _by variants to count the comparisons
+pub fn counting<T, F : FnMut(&T, &T) -> Ordering>(mut compare : F) -> impl FnMut(&T, &T) -> Ordering {
+    move |a, b| {
+        update(|s| s.comparisons += 1);
+        compare(a, b)
+    }
+}
+
+// an element counting the comparisons made between them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Counted<T>(pub T);
+
+impl<T : Ord> PartialEq for Counted<T> {
+    fn eq(&self, other : &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T : Ord> Eq for Counted<T> {}
+
+impl<T : Ord> PartialOrd for Counted<T> {
+    fn partial_cmp(&self, other : &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T : Ord> Ord for Counted<T> {
+    fn cmp(&self, other : &Self) -> Ordering {
+        update(|s| s.comparisons += 1);
+        self.0.cmp(&other.0)
+    }
+}
+
+// shapes of inputs which the sorts behave differently on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Random,
+    Sorted,
+    Reversed,
+    FewUnique,
+    OrganPipe,
+    NearlySorted // sorted, and then 1% of the elements are swapped with random ones
+}
+
+impl Shape {
+    pub const ALL : [Shape; 6] = [Shape::Random, Shape::Sorted, Shape::Reversed, Shape::FewUnique, Shape::OrganPipe, Shape::NearlySorted];
+
+    pub fn generate(&self, n : usize, seed : u64) -> Vec<u64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        match self {
+            Shape::Random => (0..n).map(|_| rng.gen()).collect(),
+            Shape::Sorted => (0..n as u64).collect(),
+            Shape::Reversed => (0..n as u64).rev().collect(),
+            Shape::FewUnique => (0..n).map(|_| rng.gen_range(0, 8)).collect(),
+            Shape::OrganPipe => (0..n as u64).map(|i| i.min(n as u64 - 1 - i)).collect(),
+            Shape::NearlySorted => {
+                let mut v : Vec<u64> = (0..n as u64).collect();
+                for _ in 0..(n / 100) {
+                    v.swap(rng.gen_range(0, n), rng.gen_range(0, n));
+                }
+                v
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{merge_sort, merge_sort_by, heap_sort, radix_sort_by_key};
+
+    #[test]
+    fn instrument_works() {
+        let mut v : Vec<_> = Shape::Reversed.generate(100, 0).into_iter().map(Counted).collect();
+        let ((), stats) = measure(|| merge_sort(&mut v));
+        assert!(v.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        let mut w = Shape::Reversed.generate(100, 0);
+        let ((), by_stats) = measure(|| merge_sort_by(&mut w, counting(|a : &u64, b : &u64| a.cmp(b))));
+        assert!(stats.comparisons > 0);
+        assert_eq!(stats.comparisons, by_stats.comparisons);
+
+        let mut v = Shape::Sorted.generate(100, 0);
+        let ((), stats) = measure(|| merge_sort_by(&mut v, counting(|a : &u64, b : &u64| a.cmp(b))));
+        assert_eq!(stats.comparisons, 99);
+        assert_eq!(stats.moves, 0);
+
+        let (_, outer) = measure(|| {
+            let (_, inner) = measure(|| Counted(1) < Counted(2));
+            assert_eq!(inner.comparisons, 1);
+            Counted(1) == Counted(2)
+        });
+        assert_eq!(outer.comparisons, 2);
+
+        assert_eq!(Shape::OrganPipe.generate(6, 0), vec![0, 1, 2, 2, 1, 0]);
+        assert!(Shape::FewUnique.generate(100, 0).iter().all(|&x| x < 8));
+
+        #[cfg(feature = "instrument")]
+        {
+            let mut v = Shape::Random.generate(1000, 1);
+            let ((), stats) = measure(|| heap_sort(&mut v));
+            assert!(stats.swaps >= 1000 - 1);
+            assert_eq!(stats.allocations, 0);
+
+            let ((), stats) = measure(|| radix_sort_by_key(&mut v, |&x| x as u8));
+            assert_eq!(stats.comparisons, 0);
+            assert_eq!(stats.allocations, 4);
+            assert_eq!(stats.moves, 2 * 1000);
+        }
+        #[cfg(not(feature = "instrument"))]
+        {
+            let mut v = Shape::Random.generate(1000, 1);
+            let ((), stats) = measure(|| { heap_sort(&mut v); radix_sort_by_key(&mut v, |&x| x as u8) });
+            assert_eq!(stats, SortStats::default());
+        }
+    }
+}
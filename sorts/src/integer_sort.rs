@@ -0,0 +1,208 @@
+use std::ptr;
+use crate::instrument;
+
+// unsigned integer keys of counting_sort and radix_sort
+pub trait Unsigned : Copy {
+    const BYTES : usize;
+
+    fn as_usize(self) -> usize;
+
+    fn byte(self, i : usize) -> usize;
+}
+
+macro_rules! impl_unsigned {
+    ($($t : ty),*) => {$(
+        impl Unsigned for $t {
+            const BYTES : usize = std::mem::size_of::<$t>();
+
+            fn as_usize(self) -> usize {
+                self as usize
+            }
+
+            fn byte(self, i : usize) -> usize {
+                ((self >> (8 * i)) & 0xff) as usize
+            }
+        }
+    )*};
+}
+
+impl_unsigned!(u8, u16, u32, u64, usize);
+
+// moves v[i] to v[dest[i]] through a buffer, which is much faster than following the cycles of the permutation
+fn permute<T>(v : &mut [T], dest : &[usize]) {
+    let n = v.len();
+    assert_eq!(dest.len(), n);
+    let mut buf : Vec<T> = Vec::with_capacity(n);
+    instrument::record_alloc::<T>(n);
+
+    // dest comes from counting_dest, so it is a permutation and every slot of the buffer is written once;
+    // only bitwise copies happen here, and the buffer keeps its length 0 so nothing is dropped twice
+    unsafe {
+        let src = v.as_mut_ptr();
+        let dst = buf.as_mut_ptr();
+        for (i, &d) in dest.iter().enumerate() {
+            ptr::copy_nonoverlapping(src.add(i), dst.add(d), 1);
+        }
+        ptr::copy_nonoverlapping(dst, src, n);
+    }
+    instrument::record_moves(2 * n);
+}
+
+// the stable destinations of the elements when they are ordered by digit(key), which is below k
+fn counting_dest<K : Copy, D : Fn(K) -> usize>(keys : &[K], k : usize, digit : D) -> Vec<usize> {
+    let mut count = vec![0; k];
+    instrument::record_alloc::<usize>(k);
+    for &x in keys.iter() {
+        count[digit(x)] += 1;
+    }
+
+    let mut sum = 0;
+    for c in count.iter_mut() {
+        sum += *c;
+        *c = sum - *c;
+    }
+
+    instrument::record_alloc::<usize>(keys.len());
+    keys.iter().map(|&x| {
+        let c = &mut count[digit(x)];
+        *c += 1;
+        *c - 1
+    }).collect()
+}
+
+// the keys are computed once up front, since a key function giving different answers must not break the permutation
+fn keys_of<T, K, F : FnMut(&T) -> K>(v : &[T], key : F) -> Vec<K> {
+    instrument::record_alloc::<K>(v.len());
+    v.iter().map(key).collect()
+}
+
+// stable, O(n + max key) time and space, the keys above 4n + 256 are sorted by radix_sort instead
+// so that the counters never take more than a few times the space of the keys
+pub fn counting_sort_by_key<T, F : FnMut(&T) -> usize>(v : &mut [T], key : F) {
+    let keys = keys_of(v, key);
+    let max = match keys.iter().max() {
+        Some(&max) => max,
+        None => return
+    };
+
+    if max < keys.len().saturating_mul(4).saturating_add(256) {
+        let dest = counting_dest(&keys, max + 1, |x| x);
+        permute(v, &dest);
+    }
+    else {
+        radix_sort_keys(v, keys);
+    }
+}
+
+pub fn counting_sort<T : Unsigned>(v : &mut [T]) {
+    counting_sort_by_key(v, |x| x.as_usize());
+}
+
+fn radix_sort_keys<T, K : Unsigned>(v : &mut [T], keys : Vec<K>) {
+    let mut keys = keys;
+    for i in 0..K::BYTES {
+        let first = match keys.first() {
+            Some(x) => x.byte(i),
+            None => return
+        };
+        // every key has the same byte here, so the pass would not move anything
+        if keys.iter().all(|x| x.byte(i) == first) {
+            continue;
+        }
+
+        let dest = counting_dest(&keys, 256, |x| x.byte(i));
+        permute(v, &dest);
+
+        // the keys follow their elements for the next passes
+        if i + 1 < K::BYTES {
+            let mut next = keys.clone();
+            instrument::record_alloc::<K>(keys.len());
+            for (&x, &d) in keys.iter().zip(dest.iter()) {
+                next[d] = x;
+            }
+            keys = next;
+        }
+    }
+}
+
+// stable, least significant byte first, O(n * K::BYTES) time
+pub fn radix_sort_by_key<T, K : Unsigned, F : FnMut(&T) -> K>(v : &mut [T], key : F) {
+    let keys = keys_of(v, key);
+    radix_sort_keys(v, keys);
+}
+
+pub fn radix_sort<T : Unsigned>(v : &mut [T]) {
+    radix_sort_by_key(v, |&x| x);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn integer_sort_works() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut small : Vec<u16> = (0..1000).map(|_| rng.gen_range(0, 300)).collect();
+        let mut expected = small.clone();
+        expected.sort();
+        counting_sort(&mut small);
+        assert_eq!(small, expected);
+
+        let mut large : Vec<u64> = (0..1000).map(|_| rng.gen()).collect();
+        let mut expected = large.clone();
+        expected.sort();
+        radix_sort(&mut large);
+        assert_eq!(large, expected);
+
+        let mut bytes : Vec<u8> = vec![3, 0, 255, 3, 1];
+        radix_sort(&mut bytes);
+        assert_eq!(bytes, vec![0, 1, 3, 3, 255]);
+
+        let mut empty : Vec<usize> = vec![];
+        counting_sort(&mut empty);
+        radix_sort(&mut empty);
+
+        let mut pairs : Vec<(u32, usize)> = (0..500).map(|i| (rng.gen_range(0, 1 << 20), i)).collect();
+        let mut expected = pairs.clone();
+        expected.sort_by_key(|p| p.0);
+        radix_sort_by_key(&mut pairs, |p| p.0);
+        assert_eq!(pairs, expected);
+
+        let mut words = vec!["pear", "fig", "banana", "kiwi", "plum"];
+        counting_sort_by_key(&mut words, |w| w.len());
+        assert_eq!(words, vec!["fig", "pear", "kiwi", "plum", "banana"]);
+
+        // keys changing between calls may give any order, but never lose or duplicate elements
+        let mut words = vec!["hello".to_string(), "world".to_string()];
+        let mut calls = 0;
+        counting_sort_by_key(&mut words, |_| { calls += 1; calls % 2 });
+        words.sort();
+        assert_eq!(words, vec!["hello", "world"]);
+
+        let mut calls = 0u64;
+        let mut words = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        radix_sort_by_key(&mut words, |_| { calls += 1; calls.wrapping_mul(0x9e37_79b9_7f4a_7c15) });
+        words.sort();
+        assert_eq!(words, vec!["a", "b", "c"]);
+
+        let mut huge = vec![usize::MAX, 0, usize::MAX - 1, 7];
+        counting_sort(&mut huge);
+        assert_eq!(huge, vec![0, 7, usize::MAX - 1, usize::MAX]);
+
+        // a single large key must not allocate a counter for every value below it
+        let mut huge = vec![usize::MAX - 1, 0];
+        counting_sort_by_key(&mut huge, |&x| x);
+        assert_eq!(huge, vec![0, usize::MAX - 1]);
+
+        let mut far : Vec<(usize, char)> = vec![(1 << 40, 'a'), (3, 'b'), (1 << 40, 'c'), (0, 'd')];
+        counting_sort_by_key(&mut far, |p| p.0);
+        assert_eq!(far, vec![(0, 'd'), (3, 'b'), (1 << 40, 'a'), (1 << 40, 'c')]);
+
+        let mut empty : Vec<String> = vec![];
+        counting_sort_by_key(&mut empty, |s| s.len());
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+use std::cmp::Ordering;
+use crate::merge_sort::insertion_sort;
+use crate::quick_sort::partition_at;
+use crate::heap_sort::heap_sort_with;
+
+// slices not longer than this are sorted by insertion sort
+const INSERTION_THRESHOLD : usize = 16;
+
+// index of the median of v[0], v[len / 2] and v[len - 1]
+fn median_of_three<T, F : FnMut(&T, &T) -> bool>(v : &[T], is_less : &mut F) -> usize {
+    let (a, b, c) = (0, v.len() / 2, v.len() - 1);
+
+    if is_less(&v[b], &v[a]) {
+        if is_less(&v[c], &v[b]) { b } else if is_less(&v[c], &v[a]) { c } else { a }
+    }
+    else if is_less(&v[c], &v[b]) {
+        if is_less(&v[c], &v[a]) { a } else { c }
+    }
+    else {
+        b
+    }
+}
+
+fn intro_sort_rec<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], depth : u32, is_less : &mut F) {
+    let mut v = v;
+    let mut depth = depth;
+    while v.len() > INSERTION_THRESHOLD {
+        // too many bad pivots, so the rest is sorted in O(n log n) time anyway
+        if depth == 0 {
+            heap_sort_with(v, &mut *is_less);
+            return;
+        }
+        depth -= 1;
+
+        let p = median_of_three(v, is_less);
+        let (lt, gt) = partition_at(v, p, is_less);
+        let (left, rest) = v.split_at_mut(lt);
+        let right = &mut rest[(gt - lt)..];
+
+        if left.len() < right.len() {
+            intro_sort_rec(left, depth, is_less);
+            v = right;
+        }
+        else {
+            intro_sort_rec(right, depth, is_less);
+            v = left;
+        }
+    }
+
+    insertion_sort(v, is_less);
+}
+
+pub(crate) fn intro_sort_with<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], mut is_less : F) {
+    let depth = 2 * (usize::BITS - v.len().leading_zeros());
+    intro_sort_rec(v, depth, &mut is_less);
+}
+
+// unstable, quick sort falling back to heap sort after 2 log n levels, so O(n log n) time in the worst case
+pub fn intro_sort<T : Ord>(v : &mut [T]) {
+    intro_sort_with(v, |a, b| a < b);
+}
+
+pub fn intro_sort_by<T, F : FnMut(&T, &T) -> Ordering>(v : &mut [T], mut compare : F) {
+    intro_sort_with(v, |a, b| compare(a, b) == Ordering::Less);
+}
+
+pub fn intro_sort_by_key<T, K : Ord, F : FnMut(&T) -> K>(v : &mut [T], mut f : F) {
+    intro_sort_with(v, |a, b| f(a) < f(b));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instrument::Shape;
+
+    #[test]
+    fn intro_sort_works() {
+        for &shape in Shape::ALL.iter() {
+            for &n in [0, 1, 2, 16, 17, 1000, 10000].iter() {
+                let mut v = shape.generate(n, 0);
+                let mut expected = v.clone();
+                expected.sort();
+
+                intro_sort(&mut v);
+                assert_eq!(v, expected);
+
+                let mut v = shape.generate(n, 1);
+                expected = v.clone();
+                expected.sort();
+                intro_sort_rec(&mut v, 1, &mut |a : &u64, b : &u64| a < b);
+                assert_eq!(v, expected);
+            }
+        }
+
+        for v in [[1, 2, 3], [1, 3, 2], [2, 1, 3], [2, 3, 1], [3, 1, 2], [3, 2, 1]].iter() {
+            let p = median_of_three(v, &mut |a : &i32, b : &i32| a < b);
+            assert_eq!(v[p], 2);
+        }
+
+        let mut v : Vec<i32> = (0..1000).collect();
+        intro_sort_by(&mut v, |a, b| b.cmp(a));
+        assert_eq!(v, (0..1000).rev().collect::<Vec<_>>());
+
+        let mut words = vec!["pear", "fig", "banana", "kiwi"];
+        intro_sort_by_key(&mut words, |w| w.len());
+        assert_eq!(words[0], "fig");
+        assert_eq!(words[3], "banana");
+    }
+}
@@ -1,21 +1,21 @@
-use heaps::BinaryHeap;
+mod instrument;
+mod merge_sort;
+mod quick_sort;
+mod heap_sort;
+mod tim_sort;
+mod intro_sort;
+mod integer_sort;
+mod select;
+mod parallel;
+mod external_sort;
 
-pub fn heap_sort<T : Ord>(arr : Vec<T>) -> Vec<T> {
-    let mut sorted = Vec::with_capacity(arr.len());
-    let mut heap = BinaryHeap::from_vec(arr);
-    while !heap.is_empty() {
-        sorted.push(heap.pop().unwrap());
-    }
-
-    sorted
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn sorts_works() {
-        assert_eq!(heap_sort(vec![5, 3, 4, 9, -1, 0]), vec![-1, 0, 3, 4, 5, 9]);
-    }
-}
+pub use instrument::*;
+pub use merge_sort::*;
+pub use quick_sort::*;
+pub use heap_sort::*;
+pub use tim_sort::*;
+pub use intro_sort::*;
+pub use integer_sort::*;
+pub use select::*;
+pub use parallel::*;
+pub use external_sort::*;
@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+use std::ptr;
+use crate::instrument;
+
+// slices not longer than this are sorted by insertion sort
+const INSERTION_THRESHOLD : usize = 20;
+
+pub(crate) fn insertion_sort<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], is_less : &mut F) {
+    for i in 1..v.len() {
+        let mut j = i;
+        while j > 0 && is_less(&v[j], &v[j - 1]) {
+            instrument::swap(v, j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+// the elements of buf[start..end] not merged yet, which are written back to dest even if the comparator panics
+pub(crate) struct Hole<T> {
+    pub(crate) start : *mut T,
+    pub(crate) end : *mut T,
+    pub(crate) dest : *mut T
+}
+
+impl<T> Drop for Hole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let len = self.end.offset_from(self.start) as usize;
+            instrument::record_moves(len);
+            ptr::copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
+
+// merges the sorted v[..mid] and v[mid..], taking the left one on ties
+pub(crate) unsafe fn merge<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], mid : usize, buf : *mut T, is_less : &mut F) {
+    let len = v.len();
+    let v = v.as_mut_ptr();
+
+    ptr::copy_nonoverlapping(v, buf, mid);
+    instrument::record_moves(mid);
+    let mut hole = Hole { start : buf, end : buf.add(mid), dest : v };
+
+    let mut right = v.add(mid);
+    let end = v.add(len);
+    while hole.start < hole.end && right < end {
+        if is_less(&*right, &*hole.start) {
+            ptr::copy_nonoverlapping(right, hole.dest, 1);
+            right = right.add(1);
+        }
+        else {
+            ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+            hole.start = hole.start.add(1);
+        }
+        hole.dest = hole.dest.add(1);
+        instrument::record_moves(1);
+    }
+}
+
+pub(crate) fn merge_sort_rec<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], buf : *mut T, is_less : &mut F) {
+    let len = v.len();
+    if len <= INSERTION_THRESHOLD {
+        insertion_sort(v, is_less);
+        return;
+    }
+
+    let mid = len / 2;
+    merge_sort_rec(&mut v[..mid], buf, is_less);
+    merge_sort_rec(&mut v[mid..], buf, is_less);
+
+    if is_less(&v[mid], &v[mid - 1]) {
+        unsafe { merge(v, mid, buf, is_less) };
+    }
+}
+
+pub(crate) fn merge_sort_with<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], mut is_less : F) {
+    // the buffer only holds bitwise copies of the elements, so its length stays 0 and nothing is dropped twice
+    let mut buf : Vec<T> = Vec::with_capacity(v.len() / 2);
+    if v.len() > INSERTION_THRESHOLD {
+        instrument::record_alloc::<T>(v.len() / 2);
+    }
+    merge_sort_rec(v, buf.as_mut_ptr(), &mut is_less);
+}
+
+// stable, O(n log n) time and n / 2 extra space
+pub fn merge_sort<T : Ord>(v : &mut [T]) {
+    merge_sort_with(v, |a, b| a < b);
+}
+
+pub fn merge_sort_by<T, F : FnMut(&T, &T) -> Ordering>(v : &mut [T], mut compare : F) {
+    merge_sort_with(v, |a, b| compare(a, b) == Ordering::Less);
+}
+
+pub fn merge_sort_by_key<T, K : Ord, F : FnMut(&T) -> K>(v : &mut [T], mut f : F) {
+    merge_sort_with(v, |a, b| f(a) < f(b));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn merge_sort_works() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for &n in [0, 1, 2, 19, 20, 21, 100, 1000].iter() {
+            let mut v : Vec<i32> = (0..n).map(|_| rng.gen_range(-50, 50)).collect();
+            let mut expected = v.clone();
+            expected.sort();
+
+            merge_sort(&mut v);
+            assert_eq!(v, expected);
+        }
+
+        let mut pairs : Vec<(i32, usize)> = (0..500).map(|i| (rng.gen_range(0, 10), i)).collect();
+        merge_sort_by_key(&mut pairs, |p| p.0);
+        assert!(pairs.windows(2).all(|w| (w[0].0, w[0].1) < (w[1].0, w[1].1)));
+
+        let mut words : Vec<String> = vec!["pear", "fig", "banana", "kiwi"].into_iter().map(String::from).collect();
+        merge_sort_by(&mut words, |a, b| b.cmp(a));
+        assert_eq!(words, vec!["pear", "kiwi", "fig", "banana"]);
+    }
+}
@@ -0,0 +1,340 @@
+use std::cmp::Ordering;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::thread;
+use rand::Rng;
+use crate::merge_sort::{merge, merge_sort_rec, merge_sort_with};
+use crate::integer_sort::Unsigned;
+
+// slices not longer than this are sorted sequentially by default
+const DEFAULT_CUTOFF : usize = 1 << 16;
+
+// number of samples taken per bucket to choose the splitters of sample sort
+const OVERSAMPLING : usize = 32;
+
+struct SendPtr<T>(*mut T);
+
+impl<T> Clone for SendPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for SendPtr<T> {}
+
+unsafe impl<T : Send> Send for SendPtr<T> {}
+unsafe impl<T : Send> Sync for SendPtr<T> {}
+
+// moves v[i] into the bucket ids[i] keeping the order in each bucket, and returns where the buckets start
+fn scatter<T : Send>(v : &mut [T], ids : &[usize], buckets : usize, threads : usize) -> Vec<usize> {
+    let n = v.len();
+    let chunk = n.div_ceil(threads).max(1);
+
+    let hists : Vec<Vec<usize>> = thread::scope(|s| {
+        let handles : Vec<_> = ids.chunks(chunk).map(|ids| s.spawn(move || {
+            let mut hist = vec![0; buckets];
+            ids.iter().for_each(|&b| hist[b] += 1);
+            hist
+        })).collect();
+
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    // the destinations of the chunks are ordered by bucket first and then by chunk, so that the scatter is stable
+    let mut offsets = vec![vec![0; buckets]; hists.len()];
+    let mut starts = Vec::with_capacity(buckets + 1);
+    let mut sum = 0;
+    for b in 0..buckets {
+        starts.push(sum);
+        for (c, hist) in hists.iter().enumerate() {
+            offsets[c][b] = sum;
+            sum += hist[b];
+        }
+    }
+    starts.push(n);
+
+    // nothing moves if every element is in the same bucket
+    if starts.windows(2).any(|w| w[1] - w[0] == n) {
+        return starts;
+    }
+
+    let mut buf : Vec<T> = Vec::with_capacity(n);
+    let src = SendPtr(v.as_mut_ptr());
+    let dst = SendPtr(buf.as_mut_ptr());
+
+    // only bitwise copies happen here, so no user code can panic in the middle
+    thread::scope(|s| {
+        for (c, (ids, mut offset)) in ids.chunks(chunk).zip(offsets).enumerate() {
+            s.spawn(move || {
+                for (k, &b) in ids.iter().enumerate() {
+                    unsafe { ptr::copy_nonoverlapping(src.0.add(c * chunk + k), dst.0.add(offset[b]), 1) };
+                    offset[b] += 1;
+                }
+            });
+        }
+    });
+    unsafe { ptr::copy_nonoverlapping(buf.as_ptr(), v.as_mut_ptr(), n) };
+
+    starts
+}
+
+// computes f for every element on the threads
+fn par_map<T : Send, R : Send, F : Fn(&T) -> R + Sync>(v : &mut [T], f : &F, threads : usize) -> Vec<R> {
+    let n = v.len();
+    let chunk = n.div_ceil(threads).max(1);
+    let mut out : Vec<R> = Vec::with_capacity(n);
+
+    thread::scope(|s| {
+        for (v, out) in v.chunks_mut(chunk).zip(out.spare_capacity_mut().chunks_mut(chunk)) {
+            s.spawn(move || {
+                for (x, r) in v.iter().zip(out.iter_mut()) {
+                    r.write(f(x));
+                }
+            });
+        }
+    });
+    // the threads have written all n results, or the scope has passed on the panic of one of them
+    unsafe { out.set_len(n) };
+
+    out
+}
+
+fn par_merge_sort_rec<T, F>(v : &mut [T], buf : &mut [MaybeUninit<T>], is_less : &F, threads : usize, cutoff : usize)
+    where T : Send, F : Fn(&T, &T) -> bool + Sync
+{
+    if threads <= 1 || v.len() <= cutoff {
+        merge_sort_rec(v, buf.as_mut_ptr() as *mut T, &mut |a, b| is_less(a, b));
+        return;
+    }
+
+    let mid = v.len() / 2;
+    {
+        let (v_left, v_right) = v.split_at_mut(mid);
+        let (buf_left, buf_right) = buf.split_at_mut(mid);
+
+        thread::scope(|s| {
+            s.spawn(|| par_merge_sort_rec(v_left, buf_left, is_less, threads / 2, cutoff));
+            par_merge_sort_rec(v_right, buf_right, is_less, threads - threads / 2, cutoff);
+        });
+    }
+
+    // the last merge runs on one thread, which bounds the speedup by O(log n)
+    if is_less(&v[mid], &v[mid - 1]) {
+        unsafe { merge(v, mid, buf.as_mut_ptr() as *mut T, &mut |a, b| is_less(a, b)) };
+    }
+}
+
+// the parallel sorts give the same result as merge_sort or radix_sort, since all of them are stable
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelSort {
+    threads : usize,
+    cutoff : usize
+}
+
+impl ParallelSort {
+    pub fn new() -> Self {
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        Self { threads, cutoff : DEFAULT_CUTOFF }
+    }
+
+    pub fn threads(mut self, threads : usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    // slices not longer than cutoff are sorted by the sequential algorithms
+    pub fn cutoff(mut self, cutoff : usize) -> Self {
+        self.cutoff = cutoff;
+        self
+    }
+
+    fn merge_sort_with<T : Send, F : Fn(&T, &T) -> bool + Sync>(&self, v : &mut [T], is_less : F) {
+        if self.threads <= 1 || v.len() <= self.cutoff {
+            merge_sort_with(v, is_less);
+            return;
+        }
+
+        let mut buf : Vec<MaybeUninit<T>> = (0..v.len()).map(|_| MaybeUninit::uninit()).collect();
+        par_merge_sort_rec(v, &mut buf, &is_less, self.threads, self.cutoff);
+    }
+
+    pub fn merge_sort<T : Ord + Send>(&self, v : &mut [T]) {
+        self.merge_sort_with(v, |a, b| a < b);
+    }
+
+    pub fn merge_sort_by<T : Send, F : Fn(&T, &T) -> Ordering + Sync>(&self, v : &mut [T], compare : F) {
+        self.merge_sort_with(v, |a, b| compare(a, b) == Ordering::Less);
+    }
+
+    pub fn merge_sort_by_key<T : Send, K : Ord, F : Fn(&T) -> K + Sync>(&self, v : &mut [T], f : F) {
+        self.merge_sort_with(v, |a, b| f(a) < f(b));
+    }
+
+    // splits v into one bucket per thread by sampled splitters, and sorts the buckets independently
+    fn sample_sort_with<T : Send + Sync, F : Fn(&T, &T) -> bool + Sync>(&self, v : &mut [T], is_less : F) {
+        if self.threads <= 1 || v.len() <= self.cutoff {
+            merge_sort_with(v, is_less);
+            return;
+        }
+
+        let buckets = self.threads;
+        let mut rng = rand::thread_rng();
+        let mut sample : Vec<usize> = (0..(buckets * OVERSAMPLING)).map(|_| rng.gen_range(0, v.len())).collect();
+        merge_sort_with(&mut sample, |&i, &j| is_less(&v[i], &v[j]));
+
+        // an element x goes to the bucket of the number of splitters not greater than x, so equal elements stay together
+        let ids = {
+            let v : &[T] = v;
+            let splitters : Vec<&T> = (1..buckets).map(|k| &v[sample[k * OVERSAMPLING]]).collect();
+            let chunk = v.len().div_ceil(self.threads);
+            let mut ids = vec![0; v.len()];
+
+            thread::scope(|s| {
+                for (v, ids) in v.chunks(chunk).zip(ids.chunks_mut(chunk)) {
+                    let (splitters, is_less) = (&splitters, &is_less);
+                    s.spawn(move || {
+                        for (x, id) in v.iter().zip(ids.iter_mut()) {
+                            *id = splitters.partition_point(|s| !is_less(x, s));
+                        }
+                    });
+                }
+            });
+
+            ids
+        };
+
+        let starts = scatter(v, &ids, buckets, self.threads);
+
+        thread::scope(|s| {
+            let mut rest : &mut [T] = v;
+            for w in starts.windows(2) {
+                let (bucket, tail) = rest.split_at_mut(w[1] - w[0]);
+                rest = tail;
+
+                let is_less = &is_less;
+                s.spawn(move || merge_sort_with(bucket, is_less));
+            }
+        });
+    }
+
+    pub fn sample_sort<T : Ord + Send + Sync>(&self, v : &mut [T]) {
+        self.sample_sort_with(v, |a, b| a < b);
+    }
+
+    pub fn sample_sort_by<T : Send + Sync, F : Fn(&T, &T) -> Ordering + Sync>(&self, v : &mut [T], compare : F) {
+        self.sample_sort_with(v, |a, b| compare(a, b) == Ordering::Less);
+    }
+
+    pub fn sample_sort_by_key<T : Send + Sync, K : Ord, F : Fn(&T) -> K + Sync>(&self, v : &mut [T], f : F) {
+        self.sample_sort_with(v, |a, b| f(a) < f(b));
+    }
+
+    // each pass of LSD radix sort scatters the elements by one byte of the keys on the threads,
+    // where the keys are computed once and scattered along with their elements
+    pub fn radix_sort_by_key<T : Send, K : Unsigned + Send, F : Fn(&T) -> K + Sync>(&self, v : &mut [T], f : F) {
+        if self.threads <= 1 || v.len() <= self.cutoff {
+            crate::integer_sort::radix_sort_by_key(v, f);
+            return;
+        }
+
+        let mut keys = par_map(v, &f, self.threads);
+        for i in 0..K::BYTES {
+            let ids = par_map(&mut keys, &|x : &K| x.byte(i), self.threads);
+            scatter(v, &ids, 256, self.threads);
+            if i + 1 < K::BYTES {
+                scatter(&mut keys, &ids, 256, self.threads);
+            }
+        }
+    }
+
+    pub fn radix_sort<T : Unsigned + Send>(&self, v : &mut [T]) {
+        self.radix_sort_by_key(v, |&x| x);
+    }
+}
+
+impl Default for ParallelSort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn par_merge_sort<T : Ord + Send>(v : &mut [T]) {
+    ParallelSort::new().merge_sort(v);
+}
+
+pub fn par_sample_sort<T : Ord + Send + Sync>(v : &mut [T]) {
+    ParallelSort::new().sample_sort(v);
+}
+
+pub fn par_radix_sort<T : Unsigned + Send>(v : &mut [T]) {
+    ParallelSort::new().radix_sort(v);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::{merge_sort_by_key, radix_sort_by_key};
+
+    #[test]
+    fn parallel_works() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sorter = ParallelSort::new().threads(4).cutoff(64);
+
+        for &n in [0, 1, 64, 65, 1000, 10000].iter() {
+            let v : Vec<(u32, usize)> = (0..n).map(|i| (rng.gen_range(0, 100), i)).collect();
+            let mut expected = v.clone();
+            merge_sort_by_key(&mut expected, |p| p.0);
+
+            let mut merged = v.clone();
+            sorter.merge_sort_by_key(&mut merged, |p| p.0);
+            assert_eq!(merged, expected);
+
+            let mut sampled = v.clone();
+            sorter.sample_sort_by_key(&mut sampled, |p| p.0);
+            assert_eq!(sampled, expected);
+
+            let mut radixed = v.clone();
+            sorter.radix_sort_by_key(&mut radixed, |p| p.0);
+            assert_eq!(radixed, expected);
+
+            let mut expected = v.clone();
+            radix_sort_by_key(&mut expected, |p| (p.0 as u64) << 32 | p.1 as u64);
+            let mut radixed = v;
+            sorter.radix_sort_by_key(&mut radixed, |p| (p.0 as u64) << 32 | p.1 as u64);
+            assert_eq!(radixed, expected);
+        }
+
+        let mut v : Vec<String> = (0..5000).map(|i| format!("{}", (i * 7919) % 5000)).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        sorter.sample_sort(&mut v);
+        assert_eq!(v, expected);
+
+        // the key function runs once per element, not once per pass
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let mut v : Vec<u64> = (0..10000).map(|_| rng.gen()).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        sorter.radix_sort_by_key(&mut v, |&x| { calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed); x });
+        assert_eq!(v, expected);
+        assert_eq!(calls.into_inner(), 10000);
+
+        let mut same = vec![3u8; 5000];
+        sorter.sample_sort(&mut same);
+        sorter.radix_sort(&mut same);
+        assert_eq!(same, vec![3u8; 5000]);
+
+        let mut v : Vec<u64> = (0..100000).map(|_| rng.gen()).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        let mut w = v.clone();
+        par_merge_sort(&mut v);
+        assert_eq!(v, expected);
+        par_radix_sort(&mut w);
+        assert_eq!(w, expected);
+        v.reverse();
+        par_sample_sort(&mut v);
+        assert_eq!(v, expected);
+    }
+}
@@ -0,0 +1,94 @@
+use std::cmp::Ordering;
+use rand::Rng;
+use crate::instrument::swap;
+
+// splits v into (< pivot, == pivot, > pivot) around v[p], and returns the bounds of the middle part
+pub(crate) fn partition_at<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], p : usize, is_less : &mut F) -> (usize, usize) {
+    swap(v, 0, p);
+
+    let (mut lt, mut i, mut gt) = (1, 1, v.len());
+    while i < gt {
+        if is_less(&v[i], &v[0]) {
+            swap(v, i, lt);
+            lt += 1;
+            i += 1;
+        }
+        else if is_less(&v[0], &v[i]) {
+            gt -= 1;
+            swap(v, i, gt);
+        }
+        else {
+            i += 1;
+        }
+    }
+
+    swap(v, 0, lt - 1);
+    (lt - 1, gt)
+}
+
+pub(crate) fn quick_sort_with<T, F : FnMut(&T, &T) -> bool, R : Rng>(v : &mut [T], is_less : &mut F, rng : &mut R) {
+    let mut v = v;
+    while v.len() > 1 {
+        let p = rng.gen_range(0, v.len());
+        let (lt, gt) = partition_at(v, p, is_less);
+        let (left, rest) = v.split_at_mut(lt);
+        let right = &mut rest[(gt - lt)..];
+
+        // recurses into the smaller side only, so that the stack depth is O(log n)
+        if left.len() < right.len() {
+            quick_sort_with(left, is_less, rng);
+            v = right;
+        }
+        else {
+            quick_sort_with(right, is_less, rng);
+            v = left;
+        }
+    }
+}
+
+// unstable, expected O(n log n) time, and O(n) time when all keys are equal
+pub fn quick_sort<T : Ord>(v : &mut [T]) {
+    quick_sort_with(v, &mut |a : &T, b : &T| a < b, &mut rand::thread_rng());
+}
+
+pub fn quick_sort_by<T, F : FnMut(&T, &T) -> Ordering>(v : &mut [T], mut compare : F) {
+    quick_sort_with(v, &mut |a : &T, b : &T| compare(a, b) == Ordering::Less, &mut rand::thread_rng());
+}
+
+pub fn quick_sort_by_key<T, K : Ord, F : FnMut(&T) -> K>(v : &mut [T], mut f : F) {
+    quick_sort_with(v, &mut |a : &T, b : &T| f(a) < f(b), &mut rand::thread_rng());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn quick_sort_works() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for &n in [0, 1, 2, 3, 100, 1000].iter() {
+            let mut v : Vec<i32> = (0..n).map(|_| rng.gen_range(-50, 50)).collect();
+            let mut expected = v.clone();
+            expected.sort();
+
+            quick_sort(&mut v);
+            assert_eq!(v, expected);
+        }
+
+        let mut same = vec![7; 10000];
+        quick_sort(&mut same);
+        assert_eq!(same, vec![7; 10000]);
+
+        let mut v : Vec<i32> = (0..1000).collect();
+        quick_sort_by(&mut v, |a, b| b.cmp(a));
+        assert_eq!(v, (0..1000).rev().collect::<Vec<_>>());
+
+        let mut words = vec!["pear", "fig", "banana", "kiwi"];
+        quick_sort_by_key(&mut words, |w| w.len());
+        assert_eq!(words[0], "fig");
+        assert_eq!(words[3], "banana");
+    }
+}
@@ -0,0 +1,191 @@
+use std::cmp::Ordering;
+use rand::Rng;
+use crate::merge_sort::insertion_sort;
+use crate::quick_sort::partition_at;
+use crate::heap_sort::{make_max_heap, sift_down_top, sort_max_heap};
+use crate::instrument::swap;
+
+// the size of the groups of median-of-medians
+const GROUP : usize = 5;
+
+// moves the medians of the groups of 5 to the front, and returns the index of the median of them
+fn median_of_medians<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], is_less : &mut F) -> usize {
+    let groups = v.len() / GROUP;
+    for g in 0..groups {
+        let group = &mut v[(g * GROUP)..((g + 1) * GROUP)];
+        insertion_sort(group, is_less);
+        swap(v, g, g * GROUP + GROUP / 2);
+    }
+
+    mom_select(&mut v[..groups], groups / 2, is_less);
+    groups / 2
+}
+
+// worst-case O(n) selection, since the median of medians has at least 3/10 of the elements on each side
+fn mom_select<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], k : usize, is_less : &mut F) {
+    let mut v = v;
+    let mut k = k;
+    loop {
+        if v.len() <= GROUP {
+            insertion_sort(v, is_less);
+            return;
+        }
+
+        let p = median_of_medians(v, is_less);
+        let (lt, gt) = partition_at(v, p, is_less);
+        if k < lt {
+            v = &mut v[..lt];
+        }
+        else if k >= gt {
+            v = &mut v[gt..];
+            k -= gt;
+        }
+        else {
+            return;
+        }
+    }
+}
+
+// randomized quickselect, switching to median-of-medians when too many pivots turn out bad
+pub(crate) fn select_with<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], k : usize, mut is_less : F) {
+    assert!(k < v.len(), "index {} out of range for slice of length {}", k, v.len());
+
+    let mut rng = rand::thread_rng();
+    let mut bad_pivots = 0;
+    let limit = 2 * (usize::BITS - v.len().leading_zeros());
+
+    let mut v = v;
+    let mut k = k;
+    while v.len() > 1 {
+        if bad_pivots > limit {
+            mom_select(v, k, &mut is_less);
+            return;
+        }
+
+        let len = v.len();
+        let p = rng.gen_range(0, len);
+        let (lt, gt) = partition_at(v, p, &mut is_less);
+        if k < lt {
+            v = &mut v[..lt];
+        }
+        else if k >= gt {
+            v = &mut v[gt..];
+            k -= gt;
+        }
+        else {
+            return;
+        }
+
+        // a pivot is bad if it leaves more than 3/4 of the range
+        if 4 * v.len() > 3 * len {
+            bad_pivots += 1;
+        }
+    }
+}
+
+// reorders v so that v[k] is the element which would be there if v were sorted, no element before it is greater,
+// and no element after it is less. expected O(n) and worst-case O(n) time
+pub fn select_nth<T : Ord>(v : &mut [T], k : usize) -> &T {
+    select_with(v, k, |a, b| a < b);
+    &v[k]
+}
+
+pub fn select_nth_by<T, F : FnMut(&T, &T) -> Ordering>(v : &mut [T], k : usize, mut compare : F) -> &T {
+    select_with(v, k, |a, b| compare(a, b) == Ordering::Less);
+    &v[k]
+}
+
+pub fn select_nth_by_key<T, K : Ord, F : FnMut(&T) -> K>(v : &mut [T], k : usize, mut f : F) -> &T {
+    select_with(v, k, |a, b| f(a) < f(b));
+    &v[k]
+}
+
+// keeps the k least elements seen so far in a max-heap over v[..k], and then sorts them in O(n log k) time
+pub(crate) fn partial_sort_with<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], k : usize, mut is_less : F) {
+    let k = k.min(v.len());
+    if k == 0 {
+        return;
+    }
+
+    make_max_heap(&mut v[..k], &mut is_less);
+    for i in k..v.len() {
+        if is_less(&v[i], &v[0]) {
+            swap(v, 0, i);
+            sift_down_top(&mut v[..k], &mut is_less);
+        }
+    }
+    sort_max_heap(&mut v[..k], &mut is_less);
+}
+
+// sorts v[..k] to the k least elements in the ascending order, leaving the rest in an unspecified order
+pub fn partial_sort<T : Ord>(v : &mut [T], k : usize) {
+    partial_sort_with(v, k, |a, b| a < b);
+}
+
+pub fn partial_sort_by<T, F : FnMut(&T, &T) -> Ordering>(v : &mut [T], k : usize, mut compare : F) {
+    partial_sort_with(v, k, |a, b| compare(a, b) == Ordering::Less);
+}
+
+pub fn partial_sort_by_key<T, K : Ord, F : FnMut(&T) -> K>(v : &mut [T], k : usize, mut f : F) {
+    partial_sort_with(v, k, |a, b| f(a) < f(b));
+}
+
+pub fn is_sorted<T : Ord>(v : &[T]) -> bool {
+    v.windows(2).all(|w| w[0] <= w[1])
+}
+
+pub fn is_sorted_by<T, F : FnMut(&T, &T) -> Ordering>(v : &[T], mut compare : F) -> bool {
+    v.windows(2).all(|w| compare(&w[0], &w[1]) != Ordering::Greater)
+}
+
+pub fn is_sorted_by_key<T, K : Ord, F : FnMut(&T) -> K>(v : &[T], mut f : F) -> bool {
+    v.windows(2).all(|w| f(&w[0]) <= f(&w[1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn select_works() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for &n in [1, 2, 5, 6, 100, 1001].iter() {
+            let v : Vec<i32> = (0..n).map(|_| rng.gen_range(-50, 50)).collect();
+            let mut sorted = v.clone();
+            sorted.sort();
+
+            for &k in [0, n / 3, n / 2, n - 1].iter() {
+                let mut w = v.clone();
+                assert_eq!(*select_nth(&mut w, k), sorted[k]);
+                assert!(w[..k].iter().all(|x| *x <= w[k]) && w[k..].iter().all(|x| *x >= w[k]));
+
+                let mut w = v.clone();
+                mom_select(&mut w, k, &mut |a : &i32, b : &i32| a < b);
+                assert_eq!(w[k], sorted[k]);
+                assert!(w[..k].iter().all(|x| *x <= w[k]) && w[k..].iter().all(|x| *x >= w[k]));
+
+                let mut w = v.clone();
+                partial_sort(&mut w, k);
+                assert_eq!(w[..k], sorted[..k]);
+            }
+        }
+
+        let mut v : Vec<i32> = (0..1000).collect();
+        assert_eq!(*select_nth_by(&mut v, 10, |a, b| b.cmp(a)), 989);
+        assert_eq!(*select_nth_by_key(&mut v, 500, |x| (x % 10, *x)), 5);
+        partial_sort_by_key(&mut v, 3, |x| -x);
+        assert_eq!(v[..3], [999, 998, 997]);
+        partial_sort_by(&mut v, 2000, |a, b| a.cmp(b));
+        assert!(is_sorted(&v));
+
+        assert!(is_sorted::<i32>(&[]));
+        assert!(is_sorted(&[1, 1, 2]));
+        assert!(!is_sorted(&[2, 1]));
+        assert!(is_sorted_by(&[3, 2, 2], |a, b| b.cmp(a)));
+        assert!(is_sorted_by_key(&["a", "bb", "cc"], |s| s.len()));
+        assert!(!is_sorted_by_key(&["bb", "a"], |s| s.len()));
+    }
+}
@@ -0,0 +1,252 @@
+use std::cmp::Ordering;
+use std::ptr;
+use std::slice;
+use crate::instrument;
+use crate::merge_sort::{insertion_sort, Hole};
+
+// runs shorter than this are extended by insertion sort, unless v is short
+const MIN_MERGE : usize = 64;
+
+// one side of a merge switches to galloping after winning this many times in a row
+const MIN_GALLOP : usize = 7;
+
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    start : usize,
+    len : usize
+}
+
+// a length between MIN_MERGE / 2 and MIN_MERGE such that n / min_run is a power of 2 or slightly less
+fn min_run(n : usize) -> usize {
+    let mut n = n;
+    let mut r = 0;
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+
+    n + r
+}
+
+// number of leading elements of s satisfying pred, which has to be true on a prefix and false after it
+fn gallop<T, P : FnMut(&T) -> bool>(s : &[T], mut pred : P) -> usize {
+    let mut hi = 1;
+    while hi <= s.len() && pred(&s[hi - 1]) {
+        hi *= 2;
+    }
+
+    let mut lo = hi / 2;
+    let mut hi = hi.min(s.len() + 1) - 1;
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(&s[mid]) { lo = mid + 1; } else { hi = mid; }
+    }
+
+    lo
+}
+
+// length of the run starting at v[0], which is reversed first if it is strictly descending
+fn find_run<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], is_less : &mut F) -> usize {
+    if v.len() < 2 {
+        return v.len();
+    }
+
+    let mut end = 2;
+    if is_less(&v[1], &v[0]) {
+        while end < v.len() && is_less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+        v[..end].reverse();
+        instrument::record_moves(end);
+    }
+    else {
+        while end < v.len() && !is_less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+    }
+
+    end
+}
+
+// merges the sorted v[..mid] and v[mid..] like merge_sort, but copies whole blocks once one side keeps winning
+unsafe fn merge_gallop<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], mid : usize, buf : *mut T, is_less : &mut F) {
+    let len = v.len();
+    let v = v.as_mut_ptr();
+
+    ptr::copy_nonoverlapping(v, buf, mid);
+    instrument::record_moves(mid);
+    let mut hole = Hole { start : buf, end : buf.add(mid), dest : v };
+
+    let mut right = v.add(mid);
+    let end = v.add(len);
+    let (mut left_wins, mut right_wins) = (0, 0);
+    while hole.start < hole.end && right < end {
+        if is_less(&*right, &*hole.start) {
+            ptr::copy_nonoverlapping(right, hole.dest, 1);
+            right = right.add(1);
+            hole.dest = hole.dest.add(1);
+            right_wins += 1;
+            left_wins = 0;
+        }
+        else {
+            ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+            hole.start = hole.start.add(1);
+            hole.dest = hole.dest.add(1);
+            left_wins += 1;
+            right_wins = 0;
+        }
+        instrument::record_moves(1);
+
+        if left_wins >= MIN_GALLOP && hole.start < hole.end && right < end {
+            let left = slice::from_raw_parts(hole.start, hole.end.offset_from(hole.start) as usize);
+            let k = gallop(left, |x| !is_less(&*right, x));
+
+            ptr::copy_nonoverlapping(hole.start, hole.dest, k);
+            hole.start = hole.start.add(k);
+            hole.dest = hole.dest.add(k);
+            instrument::record_moves(k);
+            left_wins = 0;
+        }
+        else if right_wins >= MIN_GALLOP && hole.start < hole.end && right < end {
+            let rest = slice::from_raw_parts(right, end.offset_from(right) as usize);
+            let k = gallop(rest, |x| is_less(x, &*hole.start));
+
+            // the block may overlap with its destination when fewer than k elements are left in the buffer
+            ptr::copy(right, hole.dest, k);
+            right = right.add(k);
+            hole.dest = hole.dest.add(k);
+            instrument::record_moves(k);
+            right_wins = 0;
+        }
+    }
+}
+
+// merges the adjacent runs v[..mid] and v[mid..], skipping the prefix and suffix which are already in place
+fn merge_runs<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], mid : usize, buf : *mut T, is_less : &mut F) {
+    let start = gallop(&v[..mid], |x| !is_less(&v[mid], x));
+    if start == mid {
+        return;
+    }
+    let end = mid + gallop(&v[mid..], |x| is_less(x, &v[mid - 1]));
+
+    unsafe { merge_gallop(&mut v[start..end], mid - start, buf, is_less) };
+}
+
+pub(crate) fn tim_sort_with<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], mut is_less : F) {
+    let n = v.len();
+    if n <= MIN_MERGE {
+        let run = find_run(v, &mut is_less);
+        if run < n {
+            insertion_sort(v, &mut is_less);
+        }
+        return;
+    }
+
+    let min_run = min_run(n);
+    // merges always copy their left run, which may be as long as the whole slice
+    let mut buf : Vec<T> = Vec::with_capacity(n);
+    instrument::record_alloc::<T>(n);
+
+    let mut runs : Vec<Run> = Vec::new();
+    let mut start = 0;
+    while start < n {
+        let mut len = find_run(&mut v[start..], &mut is_less);
+        if len < min_run {
+            len = min_run.min(n - start);
+            insertion_sort(&mut v[start..(start + len)], &mut is_less);
+        }
+        runs.push(Run { start, len });
+        start += len;
+
+        // keeps the lengths on the stack decreasing like Fibonacci numbers, so that the merges stay balanced
+        loop {
+            let k = runs.len();
+            let at = if k >= 3 && runs[k - 3].len <= runs[k - 2].len + runs[k - 1].len
+                || k >= 4 && runs[k - 4].len <= runs[k - 3].len + runs[k - 2].len {
+                if runs[k - 3].len < runs[k - 1].len { k - 3 } else { k - 2 }
+            }
+            else if k >= 2 && runs[k - 2].len <= runs[k - 1].len {
+                k - 2
+            }
+            else {
+                break;
+            };
+
+            merge_at(v, &mut runs, at, buf.as_mut_ptr(), &mut is_less);
+        }
+    }
+
+    while runs.len() > 1 {
+        let at = runs.len() - 2;
+        merge_at(v, &mut runs, at, buf.as_mut_ptr(), &mut is_less);
+    }
+}
+
+fn merge_at<T, F : FnMut(&T, &T) -> bool>(v : &mut [T], runs : &mut Vec<Run>, at : usize, buf : *mut T, is_less : &mut F) {
+    let (a, b) = (runs[at], runs[at + 1]);
+    merge_runs(&mut v[a.start..(b.start + b.len)], a.len, buf, is_less);
+
+    runs[at].len += b.len;
+    runs.remove(at + 1);
+}
+
+// stable, O(n log n) time in the worst case and O(n) time on inputs made of a few sorted or reversed runs
+pub fn tim_sort<T : Ord>(v : &mut [T]) {
+    tim_sort_with(v, |a, b| a < b);
+}
+
+pub fn tim_sort_by<T, F : FnMut(&T, &T) -> Ordering>(v : &mut [T], mut compare : F) {
+    tim_sort_with(v, |a, b| compare(a, b) == Ordering::Less);
+}
+
+pub fn tim_sort_by_key<T, K : Ord, F : FnMut(&T) -> K>(v : &mut [T], mut f : F) {
+    tim_sort_with(v, |a, b| f(a) < f(b));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use crate::instrument::Shape;
+
+    #[test]
+    fn tim_sort_works() {
+        for &shape in Shape::ALL.iter() {
+            for &n in [0, 1, 2, 63, 64, 65, 1000, 10000].iter() {
+                let mut v = shape.generate(n, 0);
+                let mut expected = v.clone();
+                expected.sort();
+
+                tim_sort(&mut v);
+                assert_eq!(v, expected);
+            }
+        }
+
+        assert_eq!(min_run(63), 63);
+        assert_eq!(min_run(64), 32);
+        assert_eq!(min_run(65), 33);
+        assert_eq!(gallop(&[1, 2, 3, 4, 5, 6, 7, 8, 9], |&x| x < 6), 5);
+        assert_eq!(gallop(&[1, 2, 3], |&x| x < 6), 3);
+        assert_eq!(gallop::<i32, _>(&[], |_| true), 0);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut pairs : Vec<(u32, usize)> = (0..5000).map(|i| (rng.gen_range(0, 20), i)).collect();
+        let mut expected = pairs.clone();
+        expected.sort_by_key(|p| p.0);
+        tim_sort_by_key(&mut pairs, |p| p.0);
+        assert_eq!(pairs, expected);
+
+        // long runs in both directions make the merges gallop
+        let mut runs : Vec<(u32, usize)> = (0..3000).map(|i| ((i % 1000) as u32 / 2, i)).collect();
+        runs[1000..2000].reverse();
+        let mut expected = runs.clone();
+        expected.sort_by_key(|p| p.0);
+        tim_sort_by_key(&mut runs, |p| p.0);
+        assert_eq!(runs, expected);
+
+        let mut words = vec!["pear", "fig", "banana", "kiwi"];
+        tim_sort_by(&mut words, |a, b| b.cmp(a));
+        assert_eq!(words, vec!["pear", "kiwi", "fig", "banana"]);
+    }
+}
@@ -0,0 +1,49 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use trees::{BSTree, RedBlackTree, ScapegoatTree};
+
+// BSTree degenerates into a list on sorted keys, so the sizes stay small enough for it to finish
+const SIZES : [usize; 2] = [1_000, 10_000];
+
+fn random_keys(n : usize) -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(0);
+    (0..n).map(|_| rng.gen_range(0, 1 << 40)).collect()
+}
+
+fn bench_inserts(c : &mut Criterion, name : &str, keys_of : fn(usize) -> Vec<u64>) {
+    let mut group = c.benchmark_group(name);
+
+    for &n in SIZES.iter() {
+        let keys = keys_of(n);
+
+        group.bench_with_input(BenchmarkId::new("BSTree", n), &keys, |b, keys| b.iter(|| {
+            let mut tree = BSTree::new();
+            keys.iter().for_each(|&k| { tree.insert(k, k); });
+            tree.len()
+        }));
+        group.bench_with_input(BenchmarkId::new("ScapegoatTree", n), &keys, |b, keys| b.iter(|| {
+            let mut tree = ScapegoatTree::new();
+            keys.iter().for_each(|&k| { tree.insert(k, k); });
+            tree.len()
+        }));
+        group.bench_with_input(BenchmarkId::new("RedBlackTree", n), &keys, |b, keys| b.iter(|| {
+            let mut tree = RedBlackTree::new();
+            keys.iter().for_each(|&k| { tree.insert(k, k); });
+            tree.len()
+        }));
+    }
+
+    group.finish();
+}
+
+fn sequential_insert(c : &mut Criterion) {
+    bench_inserts(c, "sequential_insert", |n| (0..n as u64).collect());
+}
+
+fn random_insert(c : &mut Criterion) {
+    bench_inserts(c, "random_insert", random_keys);
+}
+
+criterion_group!(benches, sequential_insert, random_insert);
+criterion_main!(benches);
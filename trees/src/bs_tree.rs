@@ -1,16 +1,13 @@
+use std::borrow::Borrow;
+use std::ops::{Bound, RangeBounds};
+use crate::node::*;
 
-#[derive(Debug)]
-struct Node<K, T> {
-    key : K,
-    value : T,
-    left : Option<Box<Self>>,
-    right : Option<Box<Self>>,
-    parent : *mut Self
-}
+type Link<K, V> = crate::node::Link<K, V, ()>;
+type NodePtr<K, V> = *mut Node<K, V, ()>;
 
 #[derive(Debug)]
 pub struct BSTree<K : Ord, V> {
-    root : Option<Box<Node<K, V>>>,
+    root : Link<K, V>,
     n : usize
 }
 
@@ -29,174 +26,151 @@ impl<K : Ord, V> BSTree<K, V> {
         tree
     }
 
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
     pub fn is_empty(&self) -> bool {
         self.n == 0
     }
 
-    fn locate(&self, key : &K) -> Option<&Box<Node<K, V>>> {
-        if self.is_empty() {
-            return None
-        }
-
-        let mut node = self.root.as_ref().unwrap();
-
-        loop {
-            let next;
-            if *key < node.key {
-                next = &node.left;
-            }
-            else if *key > node.key {
-                next = &node.right;
-            }
-            else {
-                break;
-            }
+    pub fn get<Q : Ord + ?Sized>(&self, key : &Q) -> Option<&V> where K : Borrow<Q> {
+        find(&self.root, key).map(|u| &u.value)
+    }
 
-            if next.is_none() {
-                return Some(node);
-            }
-            
-            node = next.as_ref().unwrap();
-        }
+    pub fn get_mut<Q : Ord + ?Sized>(&mut self, key : &Q) -> Option<&mut V> where K : Borrow<Q> {
+        find_mut(&mut self.root, key).map(|u| &mut u.value)
+    }
 
-        Some(node)
+    pub fn get_key_value<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        find(&self.root, key).map(|u| (&u.key, &u.value))
     }
-    fn locate_mut(&mut self, key : &K) -> Option<&mut Box<Node<K, V>>> {
-        if self.is_empty() {
-            return None
-        }
 
-        let mut node = self.root.as_mut().unwrap();
+    pub fn contains_key<Q : Ord + ?Sized>(&self, key : &Q) -> bool where K : Borrow<Q> {
+        find(&self.root, key).is_some()
+    }
 
-        while *key != node.key {
-            if *key < node.key {
-                if let Some(ref mut next) = node.left {
-                    node = next;
-                    continue;
-                }
-            }
-            else {
-                if let Some(ref mut next) = node.right {
-                    node = next;
-                    continue;
+    // inserts a new node and returns a pointer to it, the key must not be present
+    fn insert_new(&mut self, key : K, value : V) -> NodePtr<K, V> {
+        match search(&mut self.root, &key) {
+            Search::Found(_) => unreachable!(),
+            Search::Vacant { link, parent, .. } => {
+                self.n += 1;
+                unsafe {
+                    *link = Some(Node::new(key, value, (), parent));
+                    raw(&mut *link)
                 }
             }
+        }
+    }
 
-            return Some(node);
+    pub fn insert(&mut self, key : K, value : V) -> Option<V> {
+        if let Search::Found(u) = search(&mut self.root, &key) {
+            return unsafe { Some(std::mem::replace(&mut (*u).value, value)) };
         }
 
-        Some(node)
+        self.insert_new(key, value);
+
+        None
     }
 
-    pub fn get(&self, key : &K) -> Option<&V> {
-        let node = self.locate(key);
-        if node.is_none() {
-            return None;
-        }
+    unsafe fn remove_node(&mut self, u : NodePtr<K, V>) -> (K, V) {
+        let u = splice(&mut self.root, removal_target(u));
+        self.n -= 1;
 
-        let node = node.unwrap();
+        (u.key, u.value)
+    }
 
-        if node.key == *key {
-            Some(&node.value)
-        }
-        else {
-            None
+    pub fn remove<Q : Ord + ?Sized>(&mut self, key : &Q) -> Option<(K, V)> where K : Borrow<Q> {
+        let u : NodePtr<K, V> = find_mut(&mut self.root, key)?;
+        unsafe { Some(self.remove_node(u)) }
+    }
+
+    pub fn entry(&mut self, key : K) -> Entry<'_, K, V, Self> {
+        match search(&mut self.root, &key) {
+            Search::Found(u) => Entry::Occupied(OccupiedEntry::new(self, u, |tree, u| unsafe { tree.remove_node(u as NodePtr<K, V>) })),
+            Search::Vacant { .. } => Entry::Vacant(VacantEntry::new(self, key, |tree, key, value| unsafe { &mut (*tree.insert_new(key, value)).value }))
         }
     }
 
-    pub fn get_mut(&mut self, key : &K) -> Option<&mut V> {
-        let node = self.locate_mut(key);
-        if node.is_none() {
-            return None;
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut u = self.root.as_ref()?;
+        while let Some(l) = &u.left {
+            u = l;
         }
 
-        let node = node.unwrap();
+        Some((&u.key, &u.value))
+    }
 
-        if node.key == *key {
-            Some(&mut node.value)
-        }
-        else {
-            None
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut u = self.root.as_ref()?;
+        while let Some(r) = &u.right {
+            u = r;
         }
+
+        Some((&u.key, &u.value))
     }
 
-    pub fn insert(&mut self, key : K, value : V) -> bool {
-        if self.is_empty() {
-            self.root = Some(Box::new(Node{ key : key, value : value, left : None, right : None, parent : std::ptr::null_mut() }));
-            self.n += 1;
+    // the smallest entry with a key not below key
+    pub fn ceiling<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(lower(&self.root, Bound::Included(key))) }
+    }
 
-            return true;
-        }
+    // the largest entry with a key not above key
+    pub fn floor<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(upper(&self.root, Bound::Included(key))) }
+    }
 
-        let node = self.locate_mut(&key).unwrap();
-        if node.key == key {
-            return false;
-        }
+    pub fn successor<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(lower(&self.root, Bound::Excluded(key))) }
+    }
 
-        let node_ptr : *mut Node<K, V> = node.as_mut();
-        let next;
-        if key < node.key {
-            next = &mut node.left;
-        }
-        else {
-            next = &mut node.right;
-        }
+    pub fn predecessor<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(upper(&self.root, Bound::Excluded(key))) }
+    }
 
-        *next = Some(Box::new(Node{ key : key, value : value, left : None, right : None, parent : node_ptr }));
-        
-        self.n += 1;
+    pub fn range<Q : Ord + ?Sized, R : RangeBounds<Q>>(&self, range : R) -> Range<'_, K, V> where K : Borrow<Q> {
+        Range::new(&self.root, range)
+    }
 
-        true
+    pub fn range_mut<Q : Ord + ?Sized, R : RangeBounds<Q>>(&mut self, range : R) -> RangeMut<'_, K, V> where K : Borrow<Q> {
+        RangeMut::new(&mut self.root, range)
     }
 
-    fn splice(node : &mut Node<K, V>) {
-        let mut child = if node.left.is_some() { node.left.take() } else { node.right.take() };
+    pub fn iter(&self) -> Range<'_, K, V> {
+        Range::new::<_, K, _>(&self.root, ..)
+    }
 
-        if let Some(child) = child.as_mut() {
-            child.parent = node.parent;
-        }
+    pub fn iter_mut(&mut self) -> RangeMut<'_, K, V> {
+        RangeMut::new::<_, K, _>(&mut self.root, ..)
+    }
 
-        if node.parent != std::ptr::null_mut() {
-            let parent;
-            unsafe {
-                parent = &mut *node.parent;
-            }
-            if parent.left.as_ref().map(|a| a.key == node.key).unwrap_or(false) {
-                parent.left = child;
-            }
-            else {
-                parent.right = child;
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        unsafe {
+            let u = leftmost(raw(&mut self.root));
+            if u.is_null() {
+                return None;
             }
-        }
-
-        return;
-    }
 
-    pub fn remove(&mut self, key : &K) -> bool {
-        let node = self.locate_mut(&key);
-        if node.is_none() || node.as_ref().unwrap().key != *key {
-            return false;
+            Some(self.remove_node(u))
         }
+    }
 
-        let node = node.unwrap();
-        if node.left.is_none() || node.right.is_none() {
-            Self::splice(node);
-        }
-        else {
-            let mut s = node.right.as_mut().unwrap();
-            while let Some(ref mut next) = s.left {
-                s = next;
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        unsafe {
+            let u = rightmost(raw(&mut self.root));
+            if u.is_null() {
+                return None;
             }
 
-            std::mem::swap(&mut node.key, &mut s.key);
-            std::mem::swap(&mut node.value, &mut s.value);
-
-            Self::splice(s);
+            Some(self.remove_node(u))
         }
+    }
+}
 
-        self.n -= 1;
-
-        true
+impl<K : Ord, V> Default for BSTree<K, V> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -207,7 +181,7 @@ mod tests {
     #[test]
     fn bs_tree_works() {
         let mut bst : BSTree<i32, i32> = BSTree::new();
-        
+
         bst.insert(0, 0);
         bst.insert(5, 5);
         bst.insert(-2, -2);
@@ -231,5 +205,100 @@ mod tests {
 
         assert_eq!(bst.get(&7), Some(&7));
         assert_eq!(bst.get(&0), Some(&0));
+
+        assert_eq!(bst.len(), 6);
+        assert_eq!(bst.insert(8, 80), Some(8));
+        assert_eq!(bst.get(&8), Some(&80));
+        assert_eq!(bst.remove(&1), Some((1, 1)));
+        assert_eq!(bst.remove(&1), None);
+        assert_eq!(bst.len(), 5);
+
+        assert_eq!(bst.first_key_value(), Some((&0, &0)));
+        assert_eq!(bst.last_key_value(), Some((&10, &10)));
+        assert_eq!(bst.pop_first(), Some((0, 0)));
+        assert_eq!(bst.pop_last(), Some((10, 10)));
+        assert_eq!(bst.len(), 3);
+
+        // removing the root with a single child must move the child up
+        bst = BSTree::from_vec(vec![(3, 3), (4, 4)]);
+        assert_eq!(bst.remove(&3), Some((3, 3)));
+        assert_eq!(bst.first_key_value(), Some((&4, &4)));
+
+        let mut counts : BSTree<String, usize> = BSTree::new();
+        for word in "a b a c b a".split(' ') {
+            *counts.entry(word.to_string()).or_insert(0) += 1;
+        }
+
+        assert_eq!(counts.get("a"), Some(&3));
+        assert_eq!(counts.get("b"), Some(&2));
+        assert!(counts.contains_key("c"));
+        assert!(!counts.contains_key("d"));
+
+        counts.entry("c".to_string()).and_modify(|c| *c += 10).or_default();
+        assert_eq!(counts.get("c"), Some(&11));
+
+        if let Entry::Occupied(e) = counts.entry("b".to_string()) {
+            assert_eq!(e.remove_entry(), ("b".to_string(), 2));
+        }
+        assert_eq!(counts.len(), 2);
+
+        let mut tree = BSTree::from_vec((0..20).map(|i| (i*5, i)).collect());
+
+        assert_eq!(tree.ceiling(&12), Some((&15, &3)));
+        assert_eq!(tree.ceiling(&15), Some((&15, &3)));
+        assert_eq!(tree.floor(&12), Some((&10, &2)));
+        assert_eq!(tree.floor(&-1), None);
+        assert_eq!(tree.successor(&15), Some((&20, &4)));
+        assert_eq!(tree.predecessor(&15), Some((&10, &2)));
+        assert_eq!(tree.successor(&95), None);
+
+        assert_eq!(tree.range(12..=30).map(|(k, _)| *k).collect::<Vec<_>>(), vec![15, 20, 25, 30]);
+        assert_eq!(tree.range(12..30).rev().map(|(k, _)| *k).collect::<Vec<_>>(), vec![25, 20, 15]);
+        assert_eq!(tree.range(40..40).count(), 0);
+        assert_eq!(tree.range(..).count(), 20);
+
+        let mut ends = tree.range(0..20);
+        assert_eq!(ends.next(), Some((&0, &0)));
+        assert_eq!(ends.next_back(), Some((&15, &3)));
+        assert_eq!(ends.next(), Some((&5, &1)));
+        assert_eq!(ends.next_back(), Some((&10, &2)));
+        assert_eq!(ends.next(), None);
+        assert_eq!(ends.next_back(), None);
+
+        for (_, v) in tree.range_mut(50..) {
+            *v = 0;
+        }
+        assert_eq!(tree.iter().map(|(_, v)| *v).sum::<i32>(), (0..10).sum());
+
+        // the values handed out stay writable while the range walks on from both ends
+        let mut both : RangeMut<'_, i32, i32> = tree.range_mut(..);
+        let mut values = Vec::new();
+        while let (Some((_, a)), Some((_, b))) = (both.next(), both.next_back()) {
+            values.push(a);
+            values.push(b);
+        }
+        for v in values {
+            *v += 1;
+        }
+        let sum : Range<'_, i32, i32> = tree.iter();
+        assert_eq!(sum.map(|(_, v)| *v).sum::<i32>(), (0..10).sum::<i32>() + 20);
+
+        let mut bst = BSTree::new();
+        let mut model = std::collections::BTreeMap::new();
+        for i in 0..200u64 {
+            let k = (i * 37) % 101;
+            if i % 3 == 0 {
+                assert_eq!(bst.remove(&k), model.remove_entry(&k));
+            }
+            else {
+                assert_eq!(bst.insert(k, i), model.insert(k, i));
+            }
+            assert_eq!(bst.len(), model.len());
+        }
+
+        while let Some(e) = model.pop_first() {
+            assert_eq!(bst.pop_first(), Some(e));
+        }
+        assert!(bst.is_empty());
     }
-}
\ No newline at end of file
+}
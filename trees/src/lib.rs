@@ -1,5 +1,11 @@
+mod node;
 mod bs_tree;
 mod scapegoat_tree;
+mod treap;
+mod red_black_tree;
 
+pub use node::{Entry, OccupiedEntry, VacantEntry, Range, RangeMut};
 pub use bs_tree::*;
-pub use scapegoat_tree::*;
\ No newline at end of file
+pub use scapegoat_tree::*;
+pub use treap::*;
+pub use red_black_tree::*;
@@ -0,0 +1,465 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+
+// a node of the binary search trees, where extra is what each tree keeps for balancing (size, colour, priority, ...)
+#[derive(Debug)]
+pub(crate) struct Node<K, V, X> {
+    pub(crate) key : K,
+    pub(crate) value : V,
+    pub(crate) extra : X,
+    pub(crate) left : Link<K, V, X>,
+    pub(crate) right : Link<K, V, X>,
+    pub(crate) parent : *mut Self
+}
+
+pub(crate) type Link<K, V, X> = Option<Box<Node<K, V, X>>>;
+
+impl<K, V, X> Node<K, V, X> {
+    pub(crate) fn new(key : K, value : V, extra : X, parent : *mut Self) -> Box<Self> {
+        Box::new(Self { key, value, extra, left : None, right : None, parent })
+    }
+}
+
+pub(crate) fn raw<K, V, X>(link : &mut Link<K, V, X>) -> *mut Node<K, V, X> {
+    link.as_mut().map(|u| u.as_mut() as *mut _).unwrap_or(ptr::null_mut())
+}
+
+pub(crate) fn find<'a, K, V, X, Q>(root : &'a Link<K, V, X>, key : &Q) -> Option<&'a Node<K, V, X>>
+    where K : Borrow<Q>, Q : Ord + ?Sized
+{
+    let mut link = root;
+    while let Some(u) = link {
+        match key.cmp(u.key.borrow()) {
+            Ordering::Less => link = &u.left,
+            Ordering::Greater => link = &u.right,
+            Ordering::Equal => return Some(u)
+        }
+    }
+
+    None
+}
+
+pub(crate) fn find_mut<'a, K, V, X, Q>(root : &'a mut Link<K, V, X>, key : &Q) -> Option<&'a mut Node<K, V, X>>
+    where K : Borrow<Q>, Q : Ord + ?Sized
+{
+    let mut link = root;
+    while let Some(u) = link {
+        match key.cmp(u.key.borrow()) {
+            Ordering::Less => link = &mut u.left,
+            Ordering::Greater => link = &mut u.right,
+            Ordering::Equal => return Some(u)
+        }
+    }
+
+    None
+}
+
+// where key is or should be inserted
+pub(crate) enum Search<K, V, X> {
+    Found(*mut Node<K, V, X>),
+    Vacant { link : *mut Link<K, V, X>, parent : *mut Node<K, V, X>, depth : usize }
+}
+
+pub(crate) fn search<K : Ord, V, X>(root : &mut Link<K, V, X>, key : &K) -> Search<K, V, X> {
+    let mut link : *mut Link<K, V, X> = root;
+    let mut parent = ptr::null_mut();
+    let mut depth = 0;
+
+    unsafe {
+        while let Some(u) = (*link).as_mut() {
+            let u : *mut Node<K, V, X> = u.as_mut();
+            match key.cmp(&(*u).key) {
+                Ordering::Less => link = &mut (*u).left,
+                Ordering::Greater => link = &mut (*u).right,
+                Ordering::Equal => return Search::Found(u)
+            }
+
+            parent = u;
+            depth += 1;
+        }
+    }
+
+    Search::Vacant { link, parent, depth }
+}
+
+// the node of a link, read out of the link itself instead of through a reference to the node, since the iterators
+// may have handed out references into it; Option<Box<_>> is laid out as a nullable pointer.
+// the walks below only read through it, and the nodes they return carry the permissions of the links they come from
+unsafe fn node_of<K, V, X>(link : *const Link<K, V, X>) -> *mut Node<K, V, X> {
+    *(link as *const *mut Node<K, V, X>)
+}
+
+pub(crate) unsafe fn leftmost<K, V, X>(u : *mut Node<K, V, X>) -> *mut Node<K, V, X> {
+    let mut u = u;
+    while !u.is_null() && !node_of(ptr::addr_of!((*u).left)).is_null() {
+        u = node_of(ptr::addr_of!((*u).left));
+    }
+
+    u
+}
+
+pub(crate) unsafe fn rightmost<K, V, X>(u : *mut Node<K, V, X>) -> *mut Node<K, V, X> {
+    let mut u = u;
+    while !u.is_null() && !node_of(ptr::addr_of!((*u).right)).is_null() {
+        u = node_of(ptr::addr_of!((*u).right));
+    }
+
+    u
+}
+
+// the in-order successor of u, climbing through the parent pointers when u has no right subtree
+pub(crate) unsafe fn next<K, V, X>(u : *mut Node<K, V, X>) -> *mut Node<K, V, X> {
+    let r = node_of(ptr::addr_of!((*u).right));
+    if !r.is_null() {
+        return leftmost(r);
+    }
+
+    let mut u = u;
+    let mut p = (*u).parent;
+    while !p.is_null() && node_of(ptr::addr_of!((*p).right)) == u {
+        u = p;
+        p = (*p).parent;
+    }
+
+    p
+}
+
+pub(crate) unsafe fn prev<K, V, X>(u : *mut Node<K, V, X>) -> *mut Node<K, V, X> {
+    let l = node_of(ptr::addr_of!((*u).left));
+    if !l.is_null() {
+        return rightmost(l);
+    }
+
+    let mut u = u;
+    let mut p = (*u).parent;
+    while !p.is_null() && node_of(ptr::addr_of!((*p).left)) == u {
+        u = p;
+        p = (*p).parent;
+    }
+
+    p
+}
+
+// the smallest node whose key is not below the bound when upwards, otherwise the largest one not above it, or null
+unsafe fn bound_node<K, V, X, Q>(root : *const Link<K, V, X>, bound : Bound<&Q>, upwards : bool) -> *mut Node<K, V, X>
+    where K : Borrow<Q>, Q : Ord + ?Sized
+{
+    let mut best = ptr::null_mut();
+    let mut u = node_of(root);
+    while !u.is_null() {
+        let key = (*u).key.borrow();
+        let inside = match bound {
+            Bound::Included(q) => if upwards { key >= q } else { key <= q },
+            Bound::Excluded(q) => if upwards { key > q } else { key < q },
+            Bound::Unbounded => true
+        };
+
+        best = if inside { u } else { best };
+        u = if inside == upwards { node_of(ptr::addr_of!((*u).left)) } else { node_of(ptr::addr_of!((*u).right)) };
+    }
+
+    best
+}
+
+pub(crate) fn lower<K, V, X, Q>(root : &Link<K, V, X>, bound : Bound<&Q>) -> *const Node<K, V, X>
+    where K : Borrow<Q>, Q : Ord + ?Sized
+{
+    unsafe { bound_node(root, bound, true) }
+}
+
+pub(crate) fn upper<K, V, X, Q>(root : &Link<K, V, X>, bound : Bound<&Q>) -> *const Node<K, V, X>
+    where K : Borrow<Q>, Q : Ord + ?Sized
+{
+    unsafe { bound_node(root, bound, false) }
+}
+
+pub(crate) fn lower_mut<K, V, X, Q>(root : &mut Link<K, V, X>, bound : Bound<&Q>) -> *mut Node<K, V, X>
+    where K : Borrow<Q>, Q : Ord + ?Sized
+{
+    unsafe { bound_node(root, bound, true) }
+}
+
+pub(crate) fn upper_mut<K, V, X, Q>(root : &mut Link<K, V, X>, bound : Bound<&Q>) -> *mut Node<K, V, X>
+    where K : Borrow<Q>, Q : Ord + ?Sized
+{
+    unsafe { bound_node(root, bound, false) }
+}
+
+pub(crate) unsafe fn key_value<'a, K : 'a, V : 'a, X : 'a>(u : *const Node<K, V, X>) -> Option<(&'a K, &'a V)> {
+    u.as_ref().map(|u| (&u.key, &u.value))
+}
+
+// the link owning u, which is the root or a child link of its parent
+pub(crate) unsafe fn link_of<K, V, X>(root : &mut Link<K, V, X>, u : *mut Node<K, V, X>) -> *mut Link<K, V, X> {
+    let p = (*u).parent;
+    if p.is_null() {
+        root
+    }
+    else if raw(&mut (*p).left) == u {
+        &mut (*p).left
+    }
+    else {
+        &mut (*p).right
+    }
+}
+
+// replaces the subtree of u by sub and returns u, whose children are left untouched
+pub(crate) unsafe fn replace<K, V, X>(root : &mut Link<K, V, X>, u : *mut Node<K, V, X>, sub : Link<K, V, X>) -> Box<Node<K, V, X>> {
+    let link = link_of(root, u);
+    let mut sub = sub;
+    if let Some(s) = sub.as_mut() {
+        s.parent = (*u).parent;
+    }
+
+    std::mem::replace(&mut *link, sub).unwrap()
+}
+
+// takes u out of the tree, where u has at most one child, the returned node still points to its old parent
+pub(crate) unsafe fn splice<K, V, X>(root : &mut Link<K, V, X>, u : *mut Node<K, V, X>) -> Box<Node<K, V, X>> {
+    let child = if (*u).left.is_some() { (*u).left.take() } else { (*u).right.take() };
+
+    replace(root, u, child)
+}
+
+// swaps the entries of u and the leftmost node of its right subtree when u has two children, and returns the node to splice
+pub(crate) unsafe fn removal_target<K, V, X>(u : *mut Node<K, V, X>) -> *mut Node<K, V, X> {
+    if (*u).left.is_none() || (*u).right.is_none() {
+        return u;
+    }
+
+    let s = leftmost(raw(&mut (*u).right));
+    std::mem::swap(&mut (*u).key, &mut (*s).key);
+    std::mem::swap(&mut (*u).value, &mut (*s).value);
+
+    s
+}
+
+// the nodes between front and back, both included, in order; the nodes are type-erased like in the entries
+struct Ends<K, V> {
+    front : *mut (),
+    back : *mut (),
+    walk : unsafe fn(*mut (), bool) -> *mut (),
+    item : unsafe fn(*mut ()) -> (*const K, *mut V)
+}
+
+unsafe fn walk<K, V, X>(u : *mut (), forward : bool) -> *mut () {
+    let u = u as *mut Node<K, V, X>;
+    (if forward { next(u) } else { prev(u) }) as *mut ()
+}
+
+unsafe fn item<K, V, X>(u : *mut ()) -> (*const K, *mut V) {
+    let u = u as *mut Node<K, V, X>;
+    (ptr::addr_of!((*u).key), ptr::addr_of_mut!((*u).value))
+}
+
+impl<K, V> Ends<K, V> {
+    unsafe fn new<X, Q>(front : *const Node<K, V, X>, back : *const Node<K, V, X>) -> Self
+        where K : Borrow<Q>, Q : Ord + ?Sized
+    {
+        let empty = front.is_null() || back.is_null() || (*front).key.borrow() > (*back).key.borrow();
+        let (front, back) = if empty { (ptr::null(), ptr::null()) } else { (front, back) };
+
+        Self { front : front as *mut (), back : back as *mut (), walk : walk::<K, V, X>, item : item::<K, V, X> }
+    }
+
+    // steps one end towards the other, emptying the range once they meet
+    unsafe fn step(&mut self, forward : bool) -> Option<(*const K, *mut V)> {
+        let u = if forward { self.front } else { self.back };
+        if u.is_null() {
+            return None;
+        }
+
+        if self.front == self.back {
+            self.front = ptr::null_mut();
+            self.back = ptr::null_mut();
+        }
+        else if forward {
+            self.front = (self.walk)(u, true);
+        }
+        else {
+            self.back = (self.walk)(u, false);
+        }
+
+        Some((self.item)(u))
+    }
+}
+
+pub struct Range<'a, K, V> {
+    ends : Ends<K, V>,
+    marker : PhantomData<(&'a K, &'a V)>
+}
+
+pub struct RangeMut<'a, K, V> {
+    ends : Ends<K, V>,
+    marker : PhantomData<(&'a K, &'a mut V)>
+}
+
+impl<'a, K, V> Range<'a, K, V> {
+    pub(crate) fn new<X, Q, R>(root : &'a Link<K, V, X>, range : R) -> Self
+        where K : Borrow<Q>, Q : Ord + ?Sized, R : RangeBounds<Q>
+    {
+        let front = lower(root, range.start_bound());
+        let back = upper(root, range.end_bound());
+        Self { ends : unsafe { Ends::new(front, back) }, marker : PhantomData }
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe { self.ends.step(true).map(|(k, v)| (&*k, &*v)) }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        unsafe { self.ends.step(false).map(|(k, v)| (&*k, &*v)) }
+    }
+}
+
+impl<'a, K, V> RangeMut<'a, K, V> {
+    pub(crate) fn new<X, Q, R>(root : &'a mut Link<K, V, X>, range : R) -> Self
+        where K : Borrow<Q>, Q : Ord + ?Sized, R : RangeBounds<Q>
+    {
+        let front = lower_mut(root, range.start_bound());
+        let back = upper_mut(root, range.end_bound());
+        Self { ends : unsafe { Ends::new(front, back) }, marker : PhantomData }
+    }
+}
+
+impl<'a, K, V> Iterator for RangeMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe { self.ends.step(true).map(|(k, v)| (&*k, &mut *v)) }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for RangeMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        unsafe { self.ends.step(false).map(|(k, v)| (&*k, &mut *v)) }
+    }
+}
+
+pub enum Entry<'a, K, V, T> {
+    Occupied(OccupiedEntry<'a, K, V, T>),
+    Vacant(VacantEntry<'a, K, V, T>)
+}
+
+// the node is type-erased so that the entries do not depend on what the trees keep in the nodes
+pub struct OccupiedEntry<'a, K, V, T> {
+    tree : &'a mut T,
+    node : *mut (),
+    key : *const K,
+    value : *mut V,
+    remove : unsafe fn(&mut T, *mut ()) -> (K, V)
+}
+
+pub struct VacantEntry<'a, K, V, T> {
+    tree : &'a mut T,
+    key : K,
+    insert : fn(&mut T, K, V) -> *mut V
+}
+
+impl<'a, K, V, T> OccupiedEntry<'a, K, V, T> {
+    pub(crate) fn new<X>(tree : &'a mut T, node : *mut Node<K, V, X>, remove : unsafe fn(&mut T, *mut ()) -> (K, V)) -> Self {
+        let (key, value) = unsafe { (&(*node).key as *const K, &mut (*node).value as *mut V) };
+        Self { tree, node : node as *mut (), key, value, remove }
+    }
+
+    pub fn key(&self) -> &K {
+        unsafe { &*self.key }
+    }
+
+    pub fn get(&self) -> &V {
+        unsafe { &*self.value }
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        unsafe { &mut *self.value }
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        unsafe { &mut *self.value }
+    }
+
+    pub fn insert(&mut self, value : V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        unsafe { (self.remove)(self.tree, self.node) }
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+impl<'a, K, V, T> VacantEntry<'a, K, V, T> {
+    pub(crate) fn new(tree : &'a mut T, key : K, insert : fn(&mut T, K, V) -> *mut V) -> Self {
+        Self { tree, key, insert }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, value : V) -> &'a mut V {
+        unsafe { &mut *(self.insert)(self.tree, self.key, value) }
+    }
+}
+
+impl<'a, K, V, T> Entry<'a, K, V, T> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key()
+        }
+    }
+
+    pub fn or_insert(self, default : V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F : FnOnce() -> V>(self, default : F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default())
+        }
+    }
+
+    pub fn or_insert_with_key<F : FnOnce(&K) -> V>(self, default : F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let value = default(e.key());
+                e.insert(value)
+            }
+        }
+    }
+
+    pub fn and_modify<F : FnOnce(&mut V)>(self, f : F) -> Self {
+        match self {
+            Entry::Occupied(mut e) => {
+                f(e.get_mut());
+                Entry::Occupied(e)
+            },
+            Entry::Vacant(e) => Entry::Vacant(e)
+        }
+    }
+}
+
+impl<'a, K, V : Default, T> Entry<'a, K, V, T> {
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
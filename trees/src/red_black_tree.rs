@@ -0,0 +1,508 @@
+use std::borrow::Borrow;
+use std::ops::{Bound, RangeBounds};
+use crate::node::*;
+
+// the left-leaning red-black tree of the book, which mirrors a 2-4 tree: no red node has a red child, every path
+// from the root meets the same number of black nodes, and a black left child implies a black right child
+
+// extra is the colour of the node, which is double black only while a removal is fixed up
+type Link<K, V> = crate::node::Link<K, V, u8>;
+type NodePtr<K, V> = *mut Node<K, V, u8>;
+
+const RED : u8 = 0;
+const BLACK : u8 = 1;
+
+#[derive(Debug)]
+pub struct RedBlackTree<K : Ord, V> {
+    root : Link<K, V>,
+    n : usize
+}
+
+fn is_red<K, V>(link : &Link<K, V>) -> bool {
+    link.as_ref().map(|u| u.extra == RED).unwrap_or(false)
+}
+
+// the colour of u, where the missing nodes are black
+unsafe fn colour<K, V>(u : NodePtr<K, V>) -> u8 {
+    if u.is_null() { BLACK } else { (*u).extra }
+}
+
+unsafe fn left<K, V>(u : NodePtr<K, V>) -> NodePtr<K, V> {
+    raw(&mut (*u).left)
+}
+
+unsafe fn right<K, V>(u : NodePtr<K, V>) -> NodePtr<K, V> {
+    raw(&mut (*u).right)
+}
+
+// points the children of u back to it
+fn adopt<K, V>(u : &mut Box<Node<K, V, u8>>) {
+    let ptr : NodePtr<K, V> = u.as_mut();
+    if let Some(l) = u.left.as_mut() {
+        l.parent = ptr;
+    }
+    if let Some(r) = u.right.as_mut() {
+        r.parent = ptr;
+    }
+}
+
+// makes u red and its children, which must both exist, one step blacker
+unsafe fn push_black<K, V>(u : NodePtr<K, V>) {
+    (*u).extra -= 1;
+    (*left(u)).extra += 1;
+    (*right(u)).extra += 1;
+}
+
+// the reverse of push_black, where a missing child is the double black node being fixed up and is not touched
+unsafe fn pull_black<K, V>(u : NodePtr<K, V>) {
+    (*u).extra += 1;
+    for c in [left(u), right(u)] {
+        if !c.is_null() {
+            (*c).extra -= 1;
+        }
+    }
+}
+
+unsafe fn swap_colours<K, V>(u : NodePtr<K, V>, w : NodePtr<K, V>) {
+    std::mem::swap(&mut (*u).extra, &mut (*w).extra);
+}
+
+impl<K : Ord, V> RedBlackTree<K, V> {
+    pub fn new() -> Self {
+        Self { root : None, n : 0 }
+    }
+
+    pub fn from_vec(src : Vec<(K, V)>) -> Self {
+        let mut tree = Self::new();
+
+        for (key, value) in src {
+            tree.insert(key, value);
+        }
+
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn get<Q : Ord + ?Sized>(&self, key : &Q) -> Option<&V> where K : Borrow<Q> {
+        find(&self.root, key).map(|u| &u.value)
+    }
+
+    pub fn get_mut<Q : Ord + ?Sized>(&mut self, key : &Q) -> Option<&mut V> where K : Borrow<Q> {
+        find_mut(&mut self.root, key).map(|u| &mut u.value)
+    }
+
+    pub fn get_key_value<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        find(&self.root, key).map(|u| (&u.key, &u.value))
+    }
+
+    pub fn contains_key<Q : Ord + ?Sized>(&self, key : &Q) -> bool where K : Borrow<Q> {
+        find(&self.root, key).is_some()
+    }
+
+    // rotates u down to the left of its right child
+    unsafe fn rotate_left(&mut self, u : NodePtr<K, V>) {
+        let link = link_of(&mut self.root, u);
+        let mut u = (*link).take().unwrap();
+        let mut w = u.right.take().unwrap();
+
+        w.parent = u.parent;
+        u.right = w.left.take();
+        adopt(&mut u);
+        w.left = Some(u);
+        adopt(&mut w);
+
+        *link = Some(w);
+    }
+
+    // rotates u down to the right of its left child
+    unsafe fn rotate_right(&mut self, u : NodePtr<K, V>) {
+        let link = link_of(&mut self.root, u);
+        let mut u = (*link).take().unwrap();
+        let mut w = u.left.take().unwrap();
+
+        w.parent = u.parent;
+        u.left = w.right.take();
+        adopt(&mut u);
+        w.right = Some(u);
+        adopt(&mut w);
+
+        *link = Some(w);
+    }
+
+    unsafe fn flip_left(&mut self, u : NodePtr<K, V>) {
+        swap_colours(u, right(u));
+        self.rotate_left(u);
+    }
+
+    unsafe fn flip_right(&mut self, u : NodePtr<K, V>) {
+        swap_colours(u, left(u));
+        self.rotate_right(u);
+    }
+
+    // walks up from the new red node u, removing red-red edges and right-leaning red children
+    unsafe fn add_fixup(&mut self, u : NodePtr<K, V>) {
+        let mut u = u;
+        while (*u).extra == RED {
+            let mut w = (*u).parent;
+            if w.is_null() {
+                (*u).extra = BLACK;
+                return;
+            }
+
+            if colour(left(w)) == BLACK {
+                self.flip_left(w);
+                u = w;
+                w = (*u).parent;
+            }
+            if (*w).extra == BLACK {
+                return;
+            }
+
+            // w is red, so it is not the root
+            let g = (*w).parent;
+            if colour(right(g)) == BLACK {
+                self.flip_right(g);
+                return;
+            }
+
+            push_black(g);
+            u = g;
+        }
+    }
+
+    // inserts a new node and returns a pointer to it, the key must not be present
+    fn insert_new(&mut self, key : K, value : V) -> NodePtr<K, V> {
+        match search(&mut self.root, &key) {
+            Search::Found(_) => unreachable!(),
+            Search::Vacant { link, parent, .. } => unsafe { self.attach(link, parent, key, value) }
+        }
+    }
+
+    // hangs a new red node at the vacant link under parent and fixes the colours up
+    unsafe fn attach(&mut self, link : *mut Link<K, V>, parent : NodePtr<K, V>, key : K, value : V) -> NodePtr<K, V> {
+        *link = Some(Node::new(key, value, RED, parent));
+        let u = raw(&mut *link);
+        self.n += 1;
+        self.add_fixup(u);
+
+        u
+    }
+
+    pub fn insert(&mut self, key : K, value : V) -> Option<V> {
+        match search(&mut self.root, &key) {
+            Search::Found(u) => unsafe { Some(std::mem::replace(&mut (*u).value, value)) },
+            Search::Vacant { link, parent, .. } => {
+                unsafe { self.attach(link, parent, key, value) };
+                None
+            }
+        }
+    }
+
+    // the double black u is the left child of w, and returns the node that carries the extra black on
+    unsafe fn remove_fixup_case2(&mut self, w : NodePtr<K, V>) -> NodePtr<K, V> {
+        let v = right(w);
+        pull_black(w);
+        self.flip_left(w);
+
+        let q = right(w);
+        if colour(q) == RED {
+            self.rotate_left(w);
+            self.flip_right(v);
+            push_black(q);
+            if colour(right(v)) == RED {
+                self.flip_left(v);
+            }
+            q
+        }
+        else {
+            v
+        }
+    }
+
+    // the double black u is the right child of w, whose left child is black
+    unsafe fn remove_fixup_case3(&mut self, w : NodePtr<K, V>) -> NodePtr<K, V> {
+        let v = left(w);
+        pull_black(w);
+        self.flip_right(w);
+
+        let q = left(w);
+        if colour(q) == RED {
+            self.rotate_right(w);
+            self.flip_left(v);
+            push_black(q);
+            q
+        }
+        else if colour(left(v)) == RED {
+            push_black(v);
+            v
+        }
+        else {
+            self.flip_left(v);
+            w
+        }
+    }
+
+    // pushes the extra black of u, a child of p with colour c, up until it is absorbed;
+    // u may be missing, so its colour and parent are kept apart from it
+    unsafe fn remove_fixup(&mut self, u : NodePtr<K, V>, p : NodePtr<K, V>, c : u8) {
+        let (mut u, mut p, mut c) = (u, p, c);
+        if !u.is_null() {
+            (*u).extra = c;
+        }
+
+        while c > BLACK {
+            if p.is_null() {
+                if !u.is_null() {
+                    (*u).extra = BLACK;
+                }
+                c = BLACK;
+            }
+            else if colour(left(p)) == RED {
+                // the red sibling goes above p, which stays the parent of u
+                self.flip_right(p);
+            }
+            else {
+                u = if left(p) == u { self.remove_fixup_case2(p) } else { self.remove_fixup_case3(p) };
+                p = (*u).parent;
+                c = (*u).extra;
+            }
+        }
+
+        // restores the left-leaning property at the parent
+        if !p.is_null() && colour(right(p)) == RED && colour(left(p)) == BLACK {
+            self.flip_left(p);
+        }
+    }
+
+    unsafe fn remove_node(&mut self, u : NodePtr<K, V>) -> (K, V) {
+        let w = removal_target(u);
+        let child = if (*w).left.is_some() { left(w) } else { right(w) };
+        let (p, c) = ((*w).parent, colour(child) + (*w).extra);
+
+        let w = splice(&mut self.root, w);
+        self.n -= 1;
+        self.remove_fixup(child, p, c);
+
+        (w.key, w.value)
+    }
+
+    pub fn remove<Q : Ord + ?Sized>(&mut self, key : &Q) -> Option<(K, V)> where K : Borrow<Q> {
+        let u : NodePtr<K, V> = find_mut(&mut self.root, key)?;
+        unsafe { Some(self.remove_node(u)) }
+    }
+
+    pub fn entry(&mut self, key : K) -> Entry<'_, K, V, Self> {
+        match search(&mut self.root, &key) {
+            Search::Found(u) => Entry::Occupied(OccupiedEntry::new(self, u, |tree, u| unsafe { tree.remove_node(u as NodePtr<K, V>) })),
+            Search::Vacant { .. } => Entry::Vacant(VacantEntry::new(self, key, |tree, key, value| unsafe { &mut (*tree.insert_new(key, value)).value }))
+        }
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut u = self.root.as_ref()?;
+        while let Some(l) = &u.left {
+            u = l;
+        }
+
+        Some((&u.key, &u.value))
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut u = self.root.as_ref()?;
+        while let Some(r) = &u.right {
+            u = r;
+        }
+
+        Some((&u.key, &u.value))
+    }
+
+    // the smallest entry with a key not below key
+    pub fn ceiling<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(lower(&self.root, Bound::Included(key))) }
+    }
+
+    // the largest entry with a key not above key
+    pub fn floor<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(upper(&self.root, Bound::Included(key))) }
+    }
+
+    pub fn successor<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(lower(&self.root, Bound::Excluded(key))) }
+    }
+
+    pub fn predecessor<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(upper(&self.root, Bound::Excluded(key))) }
+    }
+
+    pub fn range<Q : Ord + ?Sized, R : RangeBounds<Q>>(&self, range : R) -> Range<'_, K, V> where K : Borrow<Q> {
+        Range::new(&self.root, range)
+    }
+
+    pub fn range_mut<Q : Ord + ?Sized, R : RangeBounds<Q>>(&mut self, range : R) -> RangeMut<'_, K, V> where K : Borrow<Q> {
+        RangeMut::new(&mut self.root, range)
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V> {
+        Range::new::<_, K, _>(&self.root, ..)
+    }
+
+    pub fn iter_mut(&mut self) -> RangeMut<'_, K, V> {
+        RangeMut::new::<_, K, _>(&mut self.root, ..)
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        unsafe {
+            let u = leftmost(raw(&mut self.root));
+            if u.is_null() {
+                return None;
+            }
+
+            Some(self.remove_node(u))
+        }
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        unsafe {
+            let u = rightmost(raw(&mut self.root));
+            if u.is_null() {
+                return None;
+            }
+
+            Some(self.remove_node(u))
+        }
+    }
+
+    fn check_at(link : &Link<K, V>, parent : NodePtr<K, V>, lo : Option<&K>, hi : Option<&K>) -> Result<usize, &'static str> {
+        let u = match link {
+            None => return Ok(1),
+            Some(u) => u
+        };
+
+        if u.parent != parent {
+            return Err("a parent pointer is wrong");
+        }
+        if lo.map(|lo| *lo >= u.key).unwrap_or(false) || hi.map(|hi| *hi <= u.key).unwrap_or(false) {
+            return Err("the keys are out of order");
+        }
+        if u.extra > BLACK {
+            return Err("a node is double black");
+        }
+        if !is_red(&u.left) && is_red(&u.right) {
+            return Err("a right child is red while the left one is black");
+        }
+        if u.extra == RED && (is_red(&u.left) || is_red(&u.right)) {
+            return Err("two red nodes are in a row");
+        }
+
+        let ptr = u.as_ref() as *const Node<K, V, u8> as NodePtr<K, V>;
+        let l = Self::check_at(&u.left, ptr, lo, Some(&u.key))?;
+        let r = Self::check_at(&u.right, ptr, Some(&u.key), hi)?;
+        if l != r {
+            return Err("the black heights differ");
+        }
+
+        Ok(l + if u.extra == RED { 0 } else { 1 })
+    }
+
+    // checks the search tree order, the colour rules and the black height, which is returned
+    pub fn check_invariants(&self) -> Result<usize, &'static str> {
+        if is_red(&self.root) {
+            return Err("the root is red");
+        }
+
+        Self::check_at(&self.root, std::ptr::null_mut(), None, None)
+    }
+}
+
+impl<K : Ord, V> Default for RedBlackTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn red_black_tree_works() {
+        let mut rbt : RedBlackTree<i32, i32> = RedBlackTree::new();
+
+        rbt.insert(0, 0);
+        rbt.insert(5, 5);
+        rbt.insert(-2, -2);
+
+        assert_eq!(rbt.get(&0), Some(&0));
+        assert_eq!(rbt.get(&5), Some(&5));
+        assert_eq!(rbt.get(&-2), Some(&-2));
+        assert_eq!(rbt.get(&3), None);
+
+        assert_eq!(rbt.remove(&0), Some((0, 0)));
+        assert_eq!(rbt.remove(&0), None);
+        assert_eq!(rbt.get(&5), Some(&5));
+        assert_eq!(rbt.get(&-2), Some(&-2));
+        assert!(rbt.check_invariants().is_ok());
+
+        // sequential insertions keep the height logarithmic
+        let mut rbt = RedBlackTree::new();
+        for i in 0..1024 {
+            rbt.insert(i, i);
+        }
+        let height = rbt.check_invariants().unwrap();
+        assert!(height <= 11);
+
+        assert_eq!(rbt.ceiling(&12), Some((&12, &12)));
+        assert_eq!(rbt.successor(&12), Some((&13, &13)));
+        assert_eq!(rbt.range(10..14).rev().map(|(k, _)| *k).collect::<Vec<_>>(), vec![13, 12, 11, 10]);
+        assert_eq!(rbt.pop_first(), Some((0, 0)));
+        assert_eq!(rbt.pop_last(), Some((1023, 1023)));
+        assert!(rbt.check_invariants().is_ok());
+
+        let mut counts : RedBlackTree<String, usize> = RedBlackTree::new();
+        for word in "a b a c b a".split(' ') {
+            *counts.entry(word.to_string()).or_insert(0) += 1;
+        }
+
+        assert_eq!(counts.get("a"), Some(&3));
+        assert_eq!(counts.get("b"), Some(&2));
+        if let Entry::Occupied(e) = counts.entry("a".to_string()) {
+            assert_eq!(e.remove_entry(), ("a".to_string(), 3));
+        }
+        assert_eq!(counts.len(), 2);
+        assert!(counts.check_invariants().is_ok());
+
+        let mut rbt = RedBlackTree::new();
+        let mut model = std::collections::BTreeMap::new();
+        for i in 0..3000u64 {
+            let k = (i * 37) % 1009;
+            match i % 7 {
+                0 | 3 => assert_eq!(rbt.remove(&k), model.remove_entry(&k)),
+                5 => assert_eq!(rbt.pop_first(), model.pop_first()),
+                6 => assert_eq!(rbt.pop_last(), model.pop_last()),
+                _ => assert_eq!(rbt.insert(k, i), model.insert(k, i))
+            }
+
+            assert_eq!(rbt.len(), model.len());
+            assert!(rbt.check_invariants().is_ok());
+        }
+
+        assert!(rbt.iter().eq(model.iter()));
+
+        // removing a missing key leaves a valid tree with the same entries
+        for n in 0..64 {
+            let mut rbt = RedBlackTree::from_vec((0..n).map(|i| (2 * i, i)).collect());
+            for k in (-1..2 * n).step_by(2) {
+                assert_eq!(rbt.remove(&k), None);
+                assert!(rbt.check_invariants().is_ok());
+            }
+            assert!(rbt.iter().map(|(k, _)| *k).eq((0..n).map(|i| 2 * i)));
+        }
+    }
+}
@@ -1,17 +1,14 @@
+use std::borrow::Borrow;
+use std::ops::{Bound, RangeBounds};
+use crate::node::*;
 
-#[derive(Debug)]
-struct Node<K, T> {
-    key : K,
-    value : T,
-    size : usize,
-    left : Option<Box<Self>>,
-    right : Option<Box<Self>>,
-    parent : *mut Self
-}
+// extra holds the size of the subtree
+type Link<K, V> = crate::node::Link<K, V, usize>;
+type NodePtr<K, V> = *mut Node<K, V, usize>;
 
 #[derive(Debug)]
 pub struct ScapegoatTree<K : Ord, V> {
-    root : Option<Box<Node<K, V>>>,
+    root : Link<K, V>,
     n : usize,
     q : usize
 }
@@ -31,351 +28,324 @@ impl<K : Ord, V> ScapegoatTree<K, V> {
         tree
     }
 
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
     pub fn is_empty(&self) -> bool {
         self.n == 0
     }
 
-    fn locate(&self, key : &K) -> (Option<&Box<Node<K, V>>>, usize) {
-        if self.is_empty() {
-            return (None, 0);
-        }
+    pub fn get<Q : Ord + ?Sized>(&self, key : &Q) -> Option<&V> where K : Borrow<Q> {
+        find(&self.root, key).map(|u| &u.value)
+    }
 
-        let mut node = self.root.as_ref().unwrap();
-        let mut depth = 1;
+    pub fn get_mut<Q : Ord + ?Sized>(&mut self, key : &Q) -> Option<&mut V> where K : Borrow<Q> {
+        find_mut(&mut self.root, key).map(|u| &mut u.value)
+    }
 
-        loop {
-            let next;
-            if *key < node.key {
-                next = &node.left;
-            }
-            else if *key > node.key {
-                next = &node.right;
-            }
-            else {
-                break;
-            }
+    pub fn get_key_value<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        find(&self.root, key).map(|u| (&u.key, &u.value))
+    }
 
-            if next.is_none() {
-                return (Some(node), depth);
-            }
-            
-            node = next.as_ref().unwrap();
-            depth += 1;
-        }
+    pub fn contains_key<Q : Ord + ?Sized>(&self, key : &Q) -> bool where K : Borrow<Q> {
+        find(&self.root, key).is_some()
+    }
 
-        (Some(node), depth)
+    fn size(link : &Link<K, V>) -> usize {
+        link.as_ref().map(|u| u.extra).unwrap_or(0)
     }
-    fn locate_mut(&mut self, key : &K) -> (Option<&mut Box<Node<K, V>>>, usize) {
-        if self.is_empty() {
-            return (None, 0)
+
+    // adds d to the sizes of u and all its ancestors
+    unsafe fn add_size(u : NodePtr<K, V>, d : isize) {
+        let mut u = u;
+        while !u.is_null() {
+            (*u).extra = ((*u).extra as isize + d) as usize;
+            u = (*u).parent;
         }
+    }
 
-        let mut node = self.root.as_mut().unwrap();
-        let mut depth = 1;
+    // inserts a new node and returns a pointer to it, the key must not be present
+    fn insert_new(&mut self, key : K, value : V) -> NodePtr<K, V> {
+        let (link, parent, depth) = match search(&mut self.root, &key) {
+            Search::Found(_) => unreachable!(),
+            Search::Vacant { link, parent, depth } => (link, parent, depth)
+        };
 
-        while *key != node.key {
-            if *key < node.key {
-                if let Some(ref mut next) = node.left {
-                    node = next;
-                    depth += 1;
-                    continue;
-                }
-            }
-            else {
-                if let Some(ref mut next) = node.right {
-                    node = next;
-                    depth += 1;
-                    continue;
-                }
-            }
-
-            return (Some(node), depth);
-        }
+        unsafe {
+            *link = Some(Node::new(key, value, 1, parent));
+            let u = raw(&mut *link);
+            Self::add_size(parent, 1);
 
-        (Some(node), depth)
-    }
+            self.n += 1;
+            self.q += 1;
 
-    pub fn get(&self, key : &K) -> Option<&V> {
-        let (node, _) = self.locate(key);
-        if node.is_none() {
-            return None;
-        }
+            if depth as f64 > (self.q as f64).log(1.5) {
+                // walk up to the scapegoat, whose child holds more than 2/3 of its subtree
+                let mut w = u;
+                while 3*(*w).extra <= 2*(*(*w).parent).extra {
+                    w = (*w).parent;
+                }
 
-        let node = node.unwrap();
+                self.rebuild((*w).parent);
+            }
 
-        if node.key == *key {
-            Some(&node.value)
-        }
-        else {
-            None
+            u
         }
     }
 
-    pub fn get_mut(&mut self, key : &K) -> Option<&mut V> {
-        let (node, _) = self.locate_mut(key);
-        if node.is_none() {
-            return None;
+    pub fn insert(&mut self, key : K, value : V) -> Option<V> {
+        if let Search::Found(u) = search(&mut self.root, &key) {
+            return unsafe { Some(std::mem::replace(&mut (*u).value, value)) };
         }
 
-        let node = node.unwrap();
+        self.insert_new(key, value);
 
-        if node.key == *key {
-            Some(&mut node.value)
-        }
-        else {
-            None
-        }
+        None
     }
 
-    unsafe fn recalc_size(node : *mut Node<K, V>) {
-        if node != std::ptr::null_mut() {
-            (*node).size = (*node).left.as_ref().map(|a| a.size).unwrap_or(0) + (*node).right.as_ref().map(|a| a.size).unwrap_or(0);
-            Self::recalc_size((*node).parent);
-        }
-    }
+    unsafe fn remove_node(&mut self, u : NodePtr<K, V>) -> (K, V) {
+        let u = splice(&mut self.root, removal_target(u));
+        Self::add_size(u.parent, -1);
 
-    pub fn insert(&mut self, key : K, value : V) -> bool {
-        if self.is_empty() {
-            self.root = Some(Box::new(Node{ key : key, value : value, size : 1, left : None, right : None, parent : std::ptr::null_mut() }));
-            self.n += 1;
+        self.n -= 1;
+
+        if self.q > 2*self.n {
+            if !self.is_empty() {
+                let r = raw(&mut self.root);
+                self.rebuild(r);
+            }
 
-            return true;
+            self.q = self.n;
         }
 
-        let q = self.q;
+        (u.key, u.value)
+    }
 
-        let (node, depth) = self.locate_mut(&key);
-        let node = node.unwrap();
+    pub fn remove<Q : Ord + ?Sized>(&mut self, key : &Q) -> Option<(K, V)> where K : Borrow<Q> {
+        let u : NodePtr<K, V> = find_mut(&mut self.root, key)?;
+        unsafe { Some(self.remove_node(u)) }
+    }
 
-        if node.key == key {
-            return false;
+    pub fn entry(&mut self, key : K) -> Entry<'_, K, V, Self> {
+        match search(&mut self.root, &key) {
+            Search::Found(u) => Entry::Occupied(OccupiedEntry::new(self, u, |tree, u| unsafe { tree.remove_node(u as NodePtr<K, V>) })),
+            Search::Vacant { .. } => Entry::Vacant(VacantEntry::new(self, key, |tree, key, value| unsafe { &mut (*tree.insert_new(key, value)).value }))
         }
+    }
 
-        let node_ptr : *mut Node<K, V> = node.as_mut();
-        let next;
-        if key < node.key {
-            next = &mut node.left;
-        }
-        else {
-            next = &mut node.right;
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut u = self.root.as_ref()?;
+        while let Some(l) = &u.left {
+            u = l;
         }
 
-        *next = Some(Box::new(Node{ key : key, value : value, size : 1, left : None, right : None, parent : node_ptr }));
+        Some((&u.key, &u.value))
+    }
 
-        unsafe {
-            Self::recalc_size(node_ptr);
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut u = self.root.as_ref()?;
+        while let Some(r) = &u.right {
+            u = r;
         }
 
-        if depth > ((q + 1) as f64).log(1.5) as usize + 1 {
-            unsafe {
-                let mut w = node_ptr;
-                let mut a = (*w).size;
-                let mut b = if (*w).parent != std::ptr::null_mut() { (*(*w).parent).size } else { 0 };
-
-                while 3*a <= 2*b {
-                    if (*w).parent == std::ptr::null_mut() { panic!() }
+        Some((&u.key, &u.value))
+    }
 
-                    w = (*w).parent;
+    // the smallest entry with a key not below key
+    pub fn ceiling<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(lower(&self.root, Bound::Included(key))) }
+    }
 
-                    if (*w).parent == std::ptr::null_mut() { panic!() }
+    // the largest entry with a key not above key
+    pub fn floor<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(upper(&self.root, Bound::Included(key))) }
+    }
 
-                    a = (*w).size;
-                    b = (*(*w).parent).size;
-                }
+    pub fn successor<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(lower(&self.root, Bound::Excluded(key))) }
+    }
 
-                w = (*w).parent;
+    pub fn predecessor<Q : Ord + ?Sized>(&self, key : &Q) -> Option<(&K, &V)> where K : Borrow<Q> {
+        unsafe { key_value(upper(&self.root, Bound::Excluded(key))) }
+    }
 
-                if w == std::ptr::null_mut() { panic!() }
+    pub fn range<Q : Ord + ?Sized, R : RangeBounds<Q>>(&self, range : R) -> Range<'_, K, V> where K : Borrow<Q> {
+        Range::new(&self.root, range)
+    }
 
-                if (*w).parent != std::ptr::null_mut() && (*(*w).parent).left.as_ref().map(|a| a.key == node.key).unwrap_or(false) {
-                    let a = self.take_box(&mut *w);
-                    self.rebuild(a, true);
-                }
-                else {
-                    let a = self.take_box(&mut *w);
-                    self.rebuild(a, false);
-                }
-            }
-        }
+    pub fn range_mut<Q : Ord + ?Sized, R : RangeBounds<Q>>(&mut self, range : R) -> RangeMut<'_, K, V> where K : Borrow<Q> {
+        RangeMut::new(&mut self.root, range)
+    }
 
-        self.n += 1;
-        self.q += 1;
+    pub fn iter(&self) -> Range<'_, K, V> {
+        Range::new::<_, K, _>(&self.root, ..)
+    }
 
-        true
+    pub fn iter_mut(&mut self) -> RangeMut<'_, K, V> {
+        RangeMut::new::<_, K, _>(&mut self.root, ..)
     }
 
-    fn splice(node : &mut Node<K, V>) -> Option<&mut Node<K, V>> {
-        let mut child = if node.left.is_some() { node.left.take() } else { node.right.take() };
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        unsafe {
+            let u = leftmost(raw(&mut self.root));
+            if u.is_null() {
+                return None;
+            }
 
-        if let Some(child) = child.as_mut() {
-            child.parent = node.parent;
+            Some(self.remove_node(u))
         }
+    }
 
-        if node.parent != std::ptr::null_mut() {
-            let parent;
-            unsafe {
-                parent = &mut *node.parent;
-            }
-            if parent.left.as_ref().map(|a| a.key == node.key).unwrap_or(false) {
-                parent.left = child;
-            }
-            else {
-                parent.right = child;
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        unsafe {
+            let u = rightmost(raw(&mut self.root));
+            if u.is_null() {
+                return None;
             }
 
-            Some(parent)
-        }
-        else {
-            None
+            Some(self.remove_node(u))
         }
     }
 
-    pub fn remove(&mut self, key : &K) -> bool {
-        let (node, _) = self.locate_mut(&key);
-        if node.is_none() || node.as_ref().unwrap().key != *key {
-            return false;
-        }
-
-        let node = node.unwrap();
-        if node.left.is_none() || node.right.is_none() {
-            let node = Self::splice(node);
-            if let Some(node) = node {
-                unsafe {
-                    Self::recalc_size(node);
-                }
+    fn select_node(&self, k : usize) -> Option<&Node<K, V, usize>> {
+        let mut k = k;
+        let mut link = &self.root;
+        while let Some(u) = link {
+            let l = Self::size(&u.left);
+            if k < l {
+                link = &u.left;
             }
-        }
-        else {
-            let mut s = node.right.as_mut().unwrap();
-            while let Some(ref mut next) = s.left {
-                s = next;
+            else if k == l {
+                return Some(u);
             }
-
-            std::mem::swap(&mut node.key, &mut s.key);
-            std::mem::swap(&mut node.value, &mut s.value);
-
-            let s = Self::splice(s);
-            if let Some(s) = s {
-                unsafe {
-                    Self::recalc_size(s);
-                }
+            else {
+                k -= l + 1;
+                link = &u.right;
             }
         }
 
-        self.n -= 1;
+        None
+    }
 
-        if self.q > 2*self.n {
-            if !self.is_empty() {
-                let r = self.root.take();
-                self.rebuild(r, false);
+    fn select_node_mut(&mut self, k : usize) -> Option<&mut Node<K, V, usize>> {
+        let mut k = k;
+        let mut link = &mut self.root;
+        while let Some(u) = link {
+            let l = Self::size(&u.left);
+            if k < l {
+                link = &mut u.left;
+            }
+            else if k == l {
+                return Some(u);
+            }
+            else {
+                k -= l + 1;
+                link = &mut u.right;
             }
-
-            self.q = self.n;
         }
 
-        true
+        None
     }
 
-    fn node_size(node : &Option<Box<Node<K, V>>>) -> usize {
-        if let Some(ref node) = node {
-            1 + Self::node_size(&node.left) + Self::node_size(&node.right)
-        }
-        else {
-            0
-        }
+    // the entry with k smaller keys
+    pub fn select(&self, k : usize) -> Option<(&K, &V)> {
+        self.select_node(k).map(|u| (&u.key, &u.value))
     }
 
-    fn take_box(&mut self, node : &mut Node<K, V>) -> Option<Box<Node<K, V>>> {
-        if node.parent == std::ptr::null_mut() {
-            self.root.take()
-        }
-        else {
-            let parent;
-            unsafe {
-                parent = &mut *node.parent;
-            }
-            if parent.left.as_ref().map(|a| a.key == node.key).unwrap_or(false) {
-                parent.left.take()
+    // the number of keys below key, or not above it when inclusive
+    fn count_below<Q : Ord + ?Sized>(&self, key : &Q, inclusive : bool) -> usize where K : Borrow<Q> {
+        let mut count = 0;
+        let mut link = &self.root;
+        while let Some(u) = link {
+            let k = u.key.borrow();
+            if k < key || (inclusive && k == key) {
+                count += Self::size(&u.left) + 1;
+                link = &u.right;
             }
             else {
-                parent.right.take()
+                link = &u.left;
             }
         }
+
+        count
     }
 
-    fn into_vec_impl(dst : &mut Vec<Option<Box<Node<K, V>>>>, node : Option<Box<Node<K, V>>>, mut i : usize) -> usize {
-        if let Some(mut node) = node {
-            let l = node.left.take();
-            let r = node.right.take();
+    // the number of keys smaller than key
+    pub fn rank<Q : Ord + ?Sized>(&self, key : &Q) -> usize where K : Borrow<Q> {
+        self.count_below(key, false)
+    }
 
-            i = Self::into_vec_impl(dst, l, i);
-            dst[i] = Some(node);
-            return Self::into_vec_impl(dst, r, i + 1);
-        }
-        else {
-            i
+    pub fn count_range<Q : Ord + ?Sized, R : RangeBounds<Q>>(&self, range : R) -> usize where K : Borrow<Q> {
+        let start = match range.start_bound() {
+            Bound::Included(a) => self.count_below(a, false),
+            Bound::Excluded(a) => self.count_below(a, true),
+            Bound::Unbounded => 0
+        };
+        let end = match range.end_bound() {
+            Bound::Included(b) => self.count_below(b, true),
+            Bound::Excluded(b) => self.count_below(b, false),
+            Bound::Unbounded => self.n
+        };
+
+        end.saturating_sub(start)
+    }
+
+    pub fn remove_at(&mut self, k : usize) -> Option<(K, V)> {
+        let u : NodePtr<K, V> = self.select_node_mut(k)?;
+        unsafe { Some(self.remove_node(u)) }
+    }
+
+    fn flatten(dst : &mut Vec<Link<K, V>>, link : Link<K, V>) {
+        if let Some(mut u) = link {
+            let l = u.left.take();
+            let r = u.right.take();
+
+            Self::flatten(dst, l);
+            dst.push(Some(u));
+            Self::flatten(dst, r);
         }
     }
 
-    fn into_vec(node : Option<Box<Node<K, V>>>) -> Vec<Option<Box<Node<K, V>>>> {
-        let mut dst = Vec::new();
-        dst.resize_with(Self::node_size(&node), Default::default);
+    fn build_balanced(src : &mut [Link<K, V>], lo : usize, hi : usize) -> Link<K, V> {
+        if lo == hi {
+            return None;
+        }
+
+        let m = lo + (hi - lo)/2;
+        let left = Self::build_balanced(src, lo, m);
+        let right = Self::build_balanced(src, m + 1, hi);
 
-        Self::into_vec_impl(&mut dst, node, 0);
+        let mut u = src[m].take().unwrap();
+        let ptr : NodePtr<K, V> = u.as_mut();
+        u.extra = Self::size(&left) + Self::size(&right) + 1;
+        u.left = left.map(|mut l| { l.parent = ptr; l });
+        u.right = right.map(|mut r| { r.parent = ptr; r });
 
-        dst
+        Some(u)
     }
-    
-    fn rebuild_balanced(arr : &mut [Option<Box<Node<K, V>>>]) -> Option<Box<Node<K, V>>> {
-        let len = arr.len();
-        let i = len / 2;
-        let mut node = arr[i].take();
 
-        let mut left = if i != 0 { Self::rebuild_balanced(&mut arr[0..i]) } else { None };
-        let mut right = if i + 1 < len { Self::rebuild_balanced(&mut arr[(i+1)..len]) } else { None };
+    // replaces the subtree of u by a perfectly balanced one
+    unsafe fn rebuild(&mut self, u : NodePtr<K, V>) {
+        let link = link_of(&mut self.root, u);
+        let parent = (*u).parent;
 
-        if let Some(ref mut l) = left {
-            l.parent = node.as_mut().unwrap().as_mut();
-        }
-        if let Some(ref mut r) = right {
-            r.parent = node.as_mut().unwrap().as_mut();
+        let mut nodes = Vec::with_capacity((*u).extra);
+        Self::flatten(&mut nodes, (*link).take());
+
+        let n = nodes.len();
+        let mut balanced = Self::build_balanced(&mut nodes, 0, n);
+        if let Some(b) = balanced.as_mut() {
+            b.parent = parent;
         }
 
-        node.as_mut().unwrap().size = left.as_ref().map(|a| a.size).unwrap_or(0) + right.as_ref().map(|a| a.size).unwrap_or(0);
-        node.as_mut().unwrap().left = left;
-        node.as_mut().unwrap().right = right;
-        
-        node
+        *link = balanced;
     }
+}
 
-    fn rebuild(&mut self, node : Option<Box<Node<K, V>>>, is_left : bool) {
-        if node.is_none() {
-            return;
-        }
-        
-        let parent;
-        if node.as_ref().unwrap().parent == std::ptr::null_mut() {
-            parent = None;
-        }
-        else {
-            unsafe { parent = Some(&mut (*node.as_ref().unwrap().parent)); }
-        }
-        
-        let mut balanced = Self::rebuild_balanced(&mut Self::into_vec(node));
-        
-        if let Some(par) = parent {
-            balanced.as_mut().unwrap().parent = par;
-
-            if is_left {
-                par.left = balanced;
-            }
-            else {
-                par.right = balanced;
-            }
-        }
-        else {
-            self.root = balanced;
-        }
+impl<K : Ord, V> Default for ScapegoatTree<K, V> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -383,10 +353,24 @@ impl<K : Ord, V> ScapegoatTree<K, V> {
 mod tests {
     use super::*;
 
+    fn check<K : Ord, V>(link : &Link<K, V>, parent : NodePtr<K, V>) -> usize {
+        match link {
+            None => 0,
+            Some(u) => {
+                assert_eq!(u.parent, parent);
+                let ptr = u.as_ref() as *const Node<K, V, usize> as NodePtr<K, V>;
+                let size = check(&u.left, ptr) + check(&u.right, ptr) + 1;
+                assert_eq!(u.extra, size);
+
+                size
+            }
+        }
+    }
+
     #[test]
     fn scapegoat_tree_works() {
         let mut bst : ScapegoatTree<i32, i32> = ScapegoatTree::new();
-        
+
         bst.insert(0, 0);
         bst.insert(5, 5);
         bst.insert(-2, -2);
@@ -421,17 +405,112 @@ mod tests {
             assert_eq!(bst.get(&i), Some(&i));
         }
 
-        println!("{:?}", bst);
+        assert_eq!(check(&bst.root, std::ptr::null_mut()), 64);
 
-        // for i in 0..1 {
-        //     bst.remove(&i);
-        // }
+        for i in 0..32 {
+            bst.remove(&i);
+        }
 
-        // for i in 0..32 {
-        //     assert_eq!(bst.get(&i), None);
-        // }
-        // for i in 32..64 {
-        //     assert_eq!(bst.get(&i), Some(&i));
-        // }
+        for i in 0..32 {
+            assert_eq!(bst.get(&i), None);
+        }
+        for i in 32..64 {
+            assert_eq!(bst.get(&i), Some(&i));
+        }
+
+        assert_eq!(check(&bst.root, std::ptr::null_mut()), 32);
+        assert_eq!(bst.first_key_value(), Some((&32, &32)));
+        assert_eq!(bst.last_key_value(), Some((&63, &63)));
+        assert_eq!(bst.insert(40, 400), Some(40));
+        assert_eq!(bst.remove(&40), Some((40, 400)));
+        assert_eq!(bst.pop_first(), Some((32, 32)));
+        assert_eq!(bst.pop_last(), Some((63, 63)));
+        assert_eq!(bst.len(), 29);
+
+        let mut counts : ScapegoatTree<String, usize> = ScapegoatTree::new();
+        for word in "a b a c b a".split(' ') {
+            *counts.entry(word.to_string()).or_insert(0) += 1;
+        }
+
+        assert_eq!(counts.get("a"), Some(&3));
+        assert_eq!(counts.get("b"), Some(&2));
+        assert!(counts.contains_key("c"));
+
+        let mut tree = ScapegoatTree::from_vec((0..20).map(|i| (i*5, i)).collect());
+
+        assert_eq!(tree.ceiling(&12), Some((&15, &3)));
+        assert_eq!(tree.ceiling(&15), Some((&15, &3)));
+        assert_eq!(tree.floor(&12), Some((&10, &2)));
+        assert_eq!(tree.floor(&-1), None);
+        assert_eq!(tree.successor(&15), Some((&20, &4)));
+        assert_eq!(tree.predecessor(&15), Some((&10, &2)));
+        assert_eq!(tree.successor(&95), None);
+
+        assert_eq!(tree.range(12..=30).map(|(k, _)| *k).collect::<Vec<_>>(), vec![15, 20, 25, 30]);
+        assert_eq!(tree.range(12..30).rev().map(|(k, _)| *k).collect::<Vec<_>>(), vec![25, 20, 15]);
+        assert_eq!(tree.range(40..40).count(), 0);
+        assert_eq!(tree.range(..).count(), 20);
+
+        let mut ends = tree.range(0..20);
+        assert_eq!(ends.next(), Some((&0, &0)));
+        assert_eq!(ends.next_back(), Some((&15, &3)));
+        assert_eq!(ends.next(), Some((&5, &1)));
+        assert_eq!(ends.next_back(), Some((&10, &2)));
+        assert_eq!(ends.next(), None);
+        assert_eq!(ends.next_back(), None);
+
+        for (_, v) in tree.range_mut(50..) {
+            *v = 0;
+        }
+        assert_eq!(tree.iter().map(|(_, v)| *v).sum::<i32>(), (0..10).sum());
+
+        let mut scores = ScapegoatTree::new();
+        for i in 0..100 {
+            scores.insert(i*2, i);
+        }
+
+        assert_eq!(scores.select(0), Some((&0, &0)));
+        assert_eq!(scores.select(37), Some((&74, &37)));
+        assert_eq!(scores.select(100), None);
+        assert_eq!(scores.rank(&74), 37);
+        assert_eq!(scores.rank(&75), 38);
+        assert_eq!(scores.rank(&1000), 100);
+        assert_eq!(scores.count_range(10..20), 5);
+        assert_eq!(scores.count_range(10..=20), 6);
+        assert_eq!(scores.count_range(..), 100);
+        assert_eq!(scores.count_range(150..), 25);
+
+        assert_eq!(scores.remove_at(10), Some((20, 10)));
+        assert_eq!(scores.select(10), Some((&22, &11)));
+        assert_eq!(scores.rank(&22), 10);
+        for _ in 0..60 {
+            scores.remove_at(0);
+        }
+        assert_eq!(scores.select(0), Some((&122, &61)));
+        assert_eq!(check(&scores.root, std::ptr::null_mut()), 39);
+
+        let mut bst = ScapegoatTree::new();
+        let mut model = std::collections::BTreeMap::new();
+        for i in 0..2000u64 {
+            let k = (i * 37) % 1009;
+            if i % 3 == 0 {
+                assert_eq!(bst.remove(&k), model.remove_entry(&k));
+            }
+            else {
+                assert_eq!(bst.insert(k, i), model.insert(k, i));
+            }
+            assert_eq!(bst.len(), model.len());
+        }
+
+        assert_eq!(check(&bst.root, std::ptr::null_mut()), model.len());
+        for (i, (k, _)) in model.iter().enumerate() {
+            assert_eq!(bst.select(i).map(|(k, _)| *k), Some(*k));
+            assert_eq!(bst.rank(k), i);
+        }
+
+        while let Some(e) = model.pop_last() {
+            assert_eq!(bst.pop_last(), Some(e));
+        }
+        assert!(bst.is_empty());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,348 @@
+use std::borrow::Borrow;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::node::*;
+
+// extra holds the priority of the node and the size of its subtree
+type Link<K, V> = crate::node::Link<K, V, (u64, usize)>;
+type NodePtr<K, V> = *mut Node<K, V, (u64, usize)>;
+
+#[derive(Debug)]
+pub struct Treap<K : Ord, V> {
+    root : Link<K, V>,
+    rng : StdRng
+}
+
+impl<K : Ord, V> Treap<K, V> {
+    // the priorities are seeded from the entropy of the system, so the shape differs between runs,
+    // with_seed and from_vec_with_seed give reproducible shapes
+    pub fn new() -> Self {
+        Self { root : None, rng : StdRng::from_entropy() }
+    }
+
+    pub fn with_seed(seed : u64) -> Self {
+        Self { root : None, rng : StdRng::seed_from_u64(seed) }
+    }
+
+    fn insert_all(mut self, src : Vec<(K, V)>) -> Self {
+        for (key, value) in src {
+            self.insert(key, value);
+        }
+
+        self
+    }
+
+    pub fn from_vec(src : Vec<(K, V)>) -> Self {
+        Self::new().insert_all(src)
+    }
+
+    pub fn from_vec_with_seed(src : Vec<(K, V)>, seed : u64) -> Self {
+        Self::with_seed(seed).insert_all(src)
+    }
+
+    fn size(link : &Link<K, V>) -> usize {
+        link.as_ref().map(|u| u.extra.1).unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn get<Q : Ord + ?Sized>(&self, key : &Q) -> Option<&V> where K : Borrow<Q> {
+        find(&self.root, key).map(|u| &u.value)
+    }
+
+    pub fn get_mut<Q : Ord + ?Sized>(&mut self, key : &Q) -> Option<&mut V> where K : Borrow<Q> {
+        find_mut(&mut self.root, key).map(|u| &mut u.value)
+    }
+
+    pub fn contains_key<Q : Ord + ?Sized>(&self, key : &Q) -> bool where K : Borrow<Q> {
+        find(&self.root, key).is_some()
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V> {
+        Range::new::<_, K, _>(&self.root, ..)
+    }
+
+    // points the children of u back to it and recomputes its size
+    fn fix(u : &mut Box<Node<K, V, (u64, usize)>>) {
+        let ptr : NodePtr<K, V> = u.as_mut();
+        if let Some(l) = u.left.as_mut() {
+            l.parent = ptr;
+        }
+        if let Some(r) = u.right.as_mut() {
+            r.parent = ptr;
+        }
+
+        u.extra.1 = Self::size(&u.left) + Self::size(&u.right) + 1;
+    }
+
+    // splits a subtree into the keys below key and the rest
+    fn split_link<Q : Ord + ?Sized>(link : Link<K, V>, key : &Q) -> (Link<K, V>, Link<K, V>) where K : Borrow<Q> {
+        match link {
+            None => (None, None),
+            Some(mut u) => {
+                if u.key.borrow() < key {
+                    let (l, r) = Self::split_link(u.right.take(), key);
+                    u.right = l;
+                    Self::fix(&mut u);
+
+                    (Some(u), r)
+                }
+                else {
+                    let (l, r) = Self::split_link(u.left.take(), key);
+                    u.left = r;
+                    Self::fix(&mut u);
+
+                    (l, Some(u))
+                }
+            }
+        }
+    }
+
+    // merges two subtrees where every key of a is below every key of b
+    fn merge_links(a : Link<K, V>, b : Link<K, V>) -> Link<K, V> {
+        match (a, b) {
+            (None, b) => b,
+            (a, None) => a,
+            (Some(mut a), Some(mut b)) => {
+                if a.extra.0 < b.extra.0 {
+                    a.right = Self::merge_links(a.right.take(), Some(b));
+                    Self::fix(&mut a);
+
+                    Some(a)
+                }
+                else {
+                    b.left = Self::merge_links(Some(a), b.left.take());
+                    Self::fix(&mut b);
+
+                    Some(b)
+                }
+            }
+        }
+    }
+
+    fn set_root(&mut self, root : Link<K, V>) {
+        self.root = root;
+        if let Some(r) = self.root.as_mut() {
+            r.parent = std::ptr::null_mut();
+        }
+    }
+
+    // rotates u down to the left of its right child
+    unsafe fn rotate_left(&mut self, u : NodePtr<K, V>) {
+        let link = link_of(&mut self.root, u);
+        let mut u = (*link).take().unwrap();
+        let mut w = u.right.take().unwrap();
+
+        w.parent = u.parent;
+        u.right = w.left.take();
+        Self::fix(&mut u);
+        w.left = Some(u);
+        Self::fix(&mut w);
+
+        *link = Some(w);
+    }
+
+    // rotates u down to the right of its left child
+    unsafe fn rotate_right(&mut self, u : NodePtr<K, V>) {
+        let link = link_of(&mut self.root, u);
+        let mut u = (*link).take().unwrap();
+        let mut w = u.left.take().unwrap();
+
+        w.parent = u.parent;
+        u.left = w.right.take();
+        Self::fix(&mut u);
+        w.right = Some(u);
+        Self::fix(&mut w);
+
+        *link = Some(w);
+    }
+
+    // adds d to the sizes of the ancestors of u
+    unsafe fn add_size_above(u : NodePtr<K, V>, d : isize) {
+        let mut p = (*u).parent;
+        while !p.is_null() {
+            (*p).extra.1 = ((*p).extra.1 as isize + d) as usize;
+            p = (*p).parent;
+        }
+    }
+
+    // rotates u up while its priority is smaller than the one of its parent
+    unsafe fn bubble_up(&mut self, u : NodePtr<K, V>) {
+        let mut p = (*u).parent;
+        while !p.is_null() && (*p).extra.0 > (*u).extra.0 {
+            if raw(&mut (*p).right) == u {
+                self.rotate_left(p);
+            }
+            else {
+                self.rotate_right(p);
+            }
+            p = (*u).parent;
+        }
+    }
+
+    // rotates u down until it is a leaf, always lifting the child of smaller priority
+    unsafe fn trickle_down(&mut self, u : NodePtr<K, V>) {
+        loop {
+            match ((*u).left.as_ref(), (*u).right.as_ref()) {
+                (None, None) => return,
+                (None, Some(_)) => self.rotate_left(u),
+                (Some(_), None) => self.rotate_right(u),
+                (Some(l), Some(r)) => if l.extra.0 < r.extra.0 { self.rotate_right(u) } else { self.rotate_left(u) }
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key : K, value : V) -> Option<V> {
+        match search(&mut self.root, &key) {
+            Search::Found(u) => unsafe { Some(std::mem::replace(&mut (*u).value, value)) },
+            Search::Vacant { link, parent, .. } => {
+                let p = self.rng.gen::<u64>();
+                unsafe {
+                    *link = Some(Node::new(key, value, (p, 1), parent));
+                    let u = raw(&mut *link);
+                    Self::add_size_above(u, 1);
+                    self.bubble_up(u);
+                }
+
+                None
+            }
+        }
+    }
+
+    unsafe fn remove_node(&mut self, u : NodePtr<K, V>) -> (K, V) {
+        self.trickle_down(u);
+        Self::add_size_above(u, -1);
+        let u = replace(&mut self.root, u, None);
+
+        (u.key, u.value)
+    }
+
+    pub fn remove<Q : Ord + ?Sized>(&mut self, key : &Q) -> Option<(K, V)> where K : Borrow<Q> {
+        let u : NodePtr<K, V> = find_mut(&mut self.root, key)?;
+        unsafe { Some(self.remove_node(u)) }
+    }
+
+    // splits into the keys below key and the rest
+    pub fn split<Q : Ord + ?Sized>(mut self, key : &Q) -> (Self, Self) where K : Borrow<Q> {
+        let (l, r) = Self::split_link(self.root.take(), key);
+
+        let mut right = Self::with_seed(self.rng.gen());
+        right.set_root(r);
+        self.set_root(l);
+
+        (self, right)
+    }
+
+    // moves every entry of other, whose keys must all be above the keys of self, into self
+    pub fn absorb(&mut self, other : Self) {
+        let mut other = other;
+
+        unsafe {
+            let last = rightmost(raw(&mut self.root));
+            let first = leftmost(raw(&mut other.root));
+            assert!(last.is_null() || first.is_null() || (*last).key < (*first).key, "the keys of the absorbed treap must be above the keys of this one");
+        }
+
+        let merged = Self::merge_links(self.root.take(), other.root.take());
+        self.set_root(merged);
+    }
+
+    pub fn join(mut self, other : Self) -> Self {
+        self.absorb(other);
+
+        self
+    }
+}
+
+impl<K : Ord, V> Default for Treap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<K : Ord, V>(link : &Link<K, V>, parent : NodePtr<K, V>) -> usize {
+        match link {
+            None => 0,
+            Some(u) => {
+                assert_eq!(u.parent, parent);
+                for c in u.left.iter().chain(u.right.iter()) {
+                    assert!(c.extra.0 >= u.extra.0);
+                }
+
+                let ptr = u.as_ref() as *const Node<K, V, (u64, usize)> as NodePtr<K, V>;
+                let size = check(&u.left, ptr) + check(&u.right, ptr) + 1;
+                assert_eq!(u.extra.1, size);
+
+                size
+            }
+        }
+    }
+
+    #[test]
+    fn treap_works() {
+        let mut treap = Treap::with_seed(1);
+
+        treap.insert(0, 0);
+        treap.insert(5, 5);
+        treap.insert(-2, -2);
+
+        assert_eq!(treap.get(&0), Some(&0));
+        assert_eq!(treap.get(&5), Some(&5));
+        assert_eq!(treap.get(&-2), Some(&-2));
+        assert_eq!(treap.get(&3), None);
+
+        assert_eq!(treap.insert(5, 50), Some(5));
+        assert_eq!(treap.remove(&0), Some((0, 0)));
+        assert_eq!(treap.remove(&0), None);
+        assert_eq!(treap.len(), 2);
+
+        let mut treap = Treap::with_seed(2);
+        let mut model = std::collections::BTreeMap::new();
+        for i in 0..2000u64 {
+            let k = (i * 37) % 1009;
+            if i % 3 == 0 {
+                assert_eq!(treap.remove(&k), model.remove_entry(&k));
+            }
+            else {
+                assert_eq!(treap.insert(k, i), model.insert(k, i));
+            }
+            assert_eq!(treap.len(), model.len());
+        }
+
+        assert_eq!(check(&treap.root, std::ptr::null_mut()), model.len());
+        assert!(treap.iter().eq(model.iter()));
+
+        let (left, right) = treap.split(&500);
+        assert_eq!(check(&left.root, std::ptr::null_mut()), model.range(..500).count());
+        assert_eq!(check(&right.root, std::ptr::null_mut()), model.range(500..).count());
+        assert!(left.iter().eq(model.range(..500)));
+        assert!(right.iter().eq(model.range(500..)));
+
+        let joined = left.join(right);
+        assert_eq!(check(&joined.root, std::ptr::null_mut()), model.len());
+        assert!(joined.iter().eq(model.iter()));
+
+        let (mut small, big) = joined.split(&0);
+        assert!(small.is_empty());
+        small.absorb(big);
+        assert_eq!(small.len(), model.len());
+
+        // the same seed gives the same shape
+        let mut b = Treap::with_seed(9);
+        for i in 0..100 {
+            b.insert(i, ());
+        }
+        let c = Treap::from_vec_with_seed((0..100).map(|i| (i, ())).collect(), 9);
+        assert_eq!(b.root.as_ref().map(|u| u.key), c.root.as_ref().map(|u| u.key));
+    }
+}